@@ -25,6 +25,10 @@ fn bench_fft(b: &mut Bencher, len: usize) {
 
 fn bench_realfft(b: &mut Bencher, len: usize) {
     let mut planner = RealFftPlanner::<f64>::new();
+    bench_realfft_planned(b, len, &mut planner);
+}
+
+fn bench_realfft_planned(b: &mut Bencher, len: usize, planner: &mut RealFftPlanner<f64>) {
     let fft = planner.plan_fft_forward(len);
 
     let mut signal = vec![0_f64; len];
@@ -39,6 +43,19 @@ fn bench_realfft(b: &mut Bencher, len: usize) {
     b.iter(|| fft.process_with_scratch(&mut signal, &mut spectrum, &mut scratch));
 }
 
+/// Times a real FFT of a given odd length, using the given algorithm from the wisdom format,
+/// for example "full" or "split 3"
+fn bench_realfft_odd(b: &mut Bencher, len: usize, algorithm: &str) {
+    let mut planner = RealFftPlanner::<f64>::new();
+    planner
+        .import_wisdom(&format!(
+            "realfft-wisdom 1\nforward {} {}\n",
+            len, algorithm
+        ))
+        .unwrap();
+    bench_realfft_planned(b, len, &mut planner);
+}
+
 /// Times just the FFT execution (not allocation and pre-calculation)
 /// for a given length
 fn bench_ifft(b: &mut Bencher, len: usize) {
@@ -57,8 +74,25 @@ fn bench_ifft(b: &mut Bencher, len: usize) {
     b.iter(|| fft.process_outofplace_with_scratch(&mut signal, &mut spectrum, &mut scratch));
 }
 
+/// Times a real iFFT of a given odd length, using the given algorithm from the wisdom format,
+/// for example "full" or "split 3"
+fn bench_realifft_odd(b: &mut Bencher, len: usize, algorithm: &str) {
+    let mut planner = RealFftPlanner::<f64>::new();
+    planner
+        .import_wisdom(&format!(
+            "realfft-wisdom 1\ninverse {} {}\n",
+            len, algorithm
+        ))
+        .unwrap();
+    bench_realifft_planned(b, len, &mut planner);
+}
+
 fn bench_realifft(b: &mut Bencher, len: usize) {
     let mut planner = RealFftPlanner::<f64>::new();
+    bench_realifft_planned(b, len, &mut planner);
+}
+
+fn bench_realifft_planned(b: &mut Bencher, len: usize, planner: &mut RealFftPlanner<f64>) {
     let fft = planner.plan_fft_inverse(len);
 
     let mut signal = vec![0_f64; len];
@@ -120,12 +154,106 @@ fn bench_range_inv(c: &mut Criterion) {
     group.finish();
 }
 
+/// Odd lengths around the thresholds for splitting into subsequences, with the radix to split them with
+const ODD_SPLIT_LENGTHS: [(usize, usize); 30] = [
+    (135, 3),
+    (441, 3),
+    (495, 3),
+    (625, 5),
+    (729, 3),
+    (1323, 3),
+    (1375, 5),
+    (2125, 5),
+    (2205, 3),
+    (3125, 5),
+    (6615, 3),
+    (15625, 5),
+    (49, 7),
+    (147, 7),
+    (343, 7),
+    (539, 7),
+    (1001, 7),
+    (2401, 7),
+    (7217, 7),
+    (121, 11),
+    (253, 11),
+    (1573, 11),
+    (1331, 11),
+    (14641, 11),
+    (169, 13),
+    (299, 13),
+    (1859, 13),
+    (2197, 13),
+    (3887, 13),
+    (28561, 13),
+];
+
+/// Prime lengths, for comparing Rader's algorithm with a complex FFT of the full length
+const ODD_PRIME_LENGTHS: [usize; 12] =
+    [31, 37, 47, 61, 101, 211, 401, 1031, 2003, 4099, 7919, 65537];
+
+fn bench_odd_prime_fw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Fw Odd prime");
+    for &len in ODD_PRIME_LENGTHS.iter() {
+        group.bench_with_input(BenchmarkId::new("Full", len), &len, |b, len| {
+            bench_realfft_odd(b, *len, "full")
+        });
+        group.bench_with_input(BenchmarkId::new("Rader", len), &len, |b, len| {
+            bench_realfft_odd(b, *len, "rader")
+        });
+    }
+    group.finish();
+}
+
+fn bench_odd_prime_inv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Inv Odd prime");
+    for &len in ODD_PRIME_LENGTHS.iter() {
+        group.bench_with_input(BenchmarkId::new("Full", len), &len, |b, len| {
+            bench_realifft_odd(b, *len, "full")
+        });
+        group.bench_with_input(BenchmarkId::new("Rader", len), &len, |b, len| {
+            bench_realifft_odd(b, *len, "rader")
+        });
+    }
+    group.finish();
+}
+
+fn bench_odd_split_fw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Fw Odd split");
+    for &(len, radix) in ODD_SPLIT_LENGTHS.iter() {
+        group.bench_with_input(BenchmarkId::new("Full", len), &len, |b, len| {
+            bench_realfft_odd(b, *len, "full")
+        });
+        group.bench_with_input(BenchmarkId::new("Split", len), &len, |b, len| {
+            bench_realfft_odd(b, *len, &format!("split {}", radix))
+        });
+    }
+    group.finish();
+}
+
+fn bench_odd_split_inv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Inv Odd split");
+    for &(len, radix) in ODD_SPLIT_LENGTHS.iter() {
+        group.bench_with_input(BenchmarkId::new("Full", len), &len, |b, len| {
+            bench_realifft_odd(b, *len, "full")
+        });
+        group.bench_with_input(BenchmarkId::new("Split", len), &len, |b, len| {
+            bench_realifft_odd(b, *len, &format!("split {}", radix))
+        });
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_pow2_fw,
     bench_range_fw,
+    bench_odd_split_fw,
+    bench_odd_prime_fw,
     bench_pow2_inv,
-    bench_range_inv
+    bench_range_inv,
+    bench_odd_split_inv,
+    bench_odd_prime_inv
 );
 
 criterion_main!(benches);
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+struct Entry<V> {
+    value: V,
+    footprint: usize,
+    last_used: u64,
+}
+
+// A cache that optionally limits the number of stored values,
+// by evicting the least recently used ones when the limit is exceeded.
+// Each value is stored together with an estimate of its memory footprint in bytes.
+pub(crate) struct LruCache<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    capacity: Option<usize>,
+    counter: u64,
+}
+
+impl<K: Hash + Eq + Copy, V: Clone> LruCache<K, V> {
+    pub(crate) fn new() -> Self {
+        LruCache {
+            entries: HashMap::new(),
+            capacity: None,
+            counter: 0,
+        }
+    }
+
+    // Get a value, and mark it as the most recently used one.
+    pub(crate) fn get(&mut self, key: &K) -> Option<V> {
+        self.counter += 1;
+        let counter = self.counter;
+        self.entries.get_mut(key).map(|entry| {
+            entry.last_used = counter;
+            entry.value.clone()
+        })
+    }
+
+    // Store a value as the most recently used one, and evict other values if the limit is exceeded.
+    pub(crate) fn insert(&mut self, key: K, value: V, footprint: usize) {
+        self.counter += 1;
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                footprint,
+                last_used: self.counter,
+            },
+        );
+        self.evict();
+    }
+
+    // Remove all values with keys matching the predicate.
+    pub(crate) fn remove_matching<F: Fn(&K) -> bool>(&mut self, predicate: F) {
+        let keys: Vec<K> = self.keys().filter(|key| predicate(key)).collect();
+        for key in keys.iter() {
+            self.entries.remove(key);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // Set the maximum number of stored values, where `None` means unlimited.
+    pub(crate) fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    pub(crate) fn keys<'a>(&'a self) -> impl Iterator<Item = K> + 'a {
+        self.entries.keys().cloned()
+    }
+
+    pub(crate) fn footprint(&self) -> usize {
+        self.entries.values().map(|entry| entry.footprint).sum()
+    }
+
+    // Evict the least recently used values until the number of values is within the limit.
+    fn evict(&mut self) {
+        let capacity = match self.capacity {
+            Some(capacity) => capacity,
+            None => return,
+        };
+        while self.entries.len() > capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            if let Some(key) = oldest {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::LruCache;
+
+    // Check that the least recently used values are evicted first
+    #[test]
+    fn lru_cache() {
+        let mut cache = LruCache::<usize, usize>::new();
+        for key in 0..5 {
+            cache.insert(key, 10 * key, key);
+        }
+        assert_eq!(cache.footprint(), 10);
+        assert_eq!(cache.get(&0), Some(0));
+        cache.set_capacity(Some(3));
+        let mut keys: Vec<usize> = cache.keys().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec![0, 3, 4]);
+
+        assert_eq!(cache.get(&3), Some(30));
+        cache.insert(7, 70, 7);
+        assert_eq!(cache.get(&4), None);
+        cache.remove_matching(|key| *key < 4);
+        assert_eq!(cache.keys().collect::<Vec<usize>>(), vec![7]);
+        cache.clear();
+        assert_eq!(cache.footprint(), 0);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{next_fast_len, RealFftPlanner, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+
+/// Selects which part of the full convolution or correlation is returned.
+/// The modes follow the definitions used by `scipy.signal.convolve`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvolutionMode {
+    /// The full result, of length `N + M - 1` for inputs of length `N` and `M`.
+    Full,
+    /// The central part of the full result, with the same length as the first input.
+    Same,
+    /// Only the part where the inputs overlap completely, of length `max(N, M) - min(N, M) + 1`.
+    Valid,
+}
+
+/// Convolves and correlates real-valued signals using real FFTs.
+///
+/// The inputs are zero-padded to a length that is at least as long as the full result,
+/// and that can be transformed efficiently. The plans are cached by the inner planner,
+/// so when performing more than one convolution it is advisable to reuse the same convolver.
+pub struct Convolver<T: FftNum> {
+    planner: RealFftPlanner<T>,
+}
+
+impl<T: FftNum> Convolver<T> {
+    /// Create a new convolver.
+    pub fn new() -> Self {
+        Self {
+            planner: RealFftPlanner::new(),
+        }
+    }
+
+    /// Get the length of the result of convolving or correlating signals of length `first_len` and `second_len`.
+    pub fn output_len(first_len: usize, second_len: usize, mode: ConvolutionMode) -> usize {
+        if first_len == 0 || second_len == 0 {
+            return 0;
+        }
+        match mode {
+            ConvolutionMode::Full => first_len + second_len - 1,
+            ConvolutionMode::Same => first_len,
+            ConvolutionMode::Valid => first_len.max(second_len) - first_len.min(second_len) + 1,
+        }
+    }
+
+    /// Convolve two real-valued signals, and return the part of the result selected by `mode`.
+    /// The result is empty if either of the signals is empty.
+    /// An error is returned if one of the inner transforms fails, for example when the signals contain NaN or infinite values.
+    pub fn convolve(&mut self, first: &[T], second: &[T], mode: ConvolutionMode) -> Res<Vec<T>> {
+        self.process(first, second, false, mode)
+    }
+
+    /// Cross-correlate two real-valued signals, and return the part of the result selected by `mode`.
+    /// This is the convolution of `first` with `second` in reverse order,
+    /// so that the value at index `second.len() - 1` of the full result corresponds to zero lag.
+    /// This is the center of the full result only when the signals have the same length.
+    /// The result is empty if either of the signals is empty.
+    /// An error is returned if one of the inner transforms fails, for example when the signals contain NaN or infinite values.
+    pub fn correlate(&mut self, first: &[T], second: &[T], mode: ConvolutionMode) -> Res<Vec<T>> {
+        self.process(first, second, true, mode)
+    }
+
+    fn process(
+        &mut self,
+        first: &[T],
+        second: &[T],
+        reverse_second: bool,
+        mode: ConvolutionMode,
+    ) -> Res<Vec<T>> {
+        if first.is_empty() || second.is_empty() {
+            return Ok(Vec::new());
+        }
+        let full_len = first.len() + second.len() - 1;
+        let fft_len = next_fast_len(full_len);
+        let r2c = self.planner.plan_fft_forward(fft_len);
+        let c2r = self.planner.plan_fft_inverse(fft_len);
+        let mut scratch = vec![Complex::zero(); r2c.get_scratch_len().max(c2r.get_scratch_len())];
+
+        let mut buffer = r2c.make_input_vec();
+        buffer[..first.len()].copy_from_slice(first);
+        let mut first_spectrum = r2c.make_output_vec();
+        r2c.process_with_scratch(&mut buffer, &mut first_spectrum, &mut scratch)?;
+
+        for val in buffer.iter_mut() {
+            *val = T::zero();
+        }
+        if reverse_second {
+            for (val, sample) in buffer.iter_mut().zip(second.iter().rev()) {
+                *val = *sample;
+            }
+        } else {
+            buffer[..second.len()].copy_from_slice(second);
+        }
+        let mut second_spectrum = r2c.make_output_vec();
+        r2c.process_with_scratch(&mut buffer, &mut second_spectrum, &mut scratch)?;
+
+        let scale = T::one() / T::from_usize(fft_len).unwrap();
+        for (a, b) in first_spectrum.iter_mut().zip(second_spectrum.iter()) {
+            *a = *a * b * scale;
+        }
+        c2r.process_with_scratch(&mut first_spectrum, &mut buffer, &mut scratch)?;
+
+        let output_len = Self::output_len(first.len(), second.len(), mode);
+        let start = (full_len - output_len) / 2;
+        buffer.truncate(start + output_len);
+        buffer.drain(..start);
+        Ok(buffer)
+    }
+}
+
+impl<T: FftNum> Default for Convolver<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConvolutionMode, Convolver, FftError};
+    use rand::Rng;
+
+    fn naive_convolve(first: &[f64], second: &[f64]) -> Vec<f64> {
+        let mut result = vec![0.0; first.len() + second.len() - 1];
+        for (n, a) in first.iter().enumerate() {
+            for (m, b) in second.iter().enumerate() {
+                result[n + m] += a * b;
+            }
+        }
+        result
+    }
+
+    // Compare convolution and correlation with direct sums, for all modes
+    #[test]
+    fn convolve_correlate() {
+        let mut convolver = Convolver::<f64>::new();
+        let mut rng = rand::thread_rng();
+        for &(first_len, second_len) in
+            [(1, 1), (10, 3), (3, 10), (7, 7), (100, 17), (16, 41)].iter()
+        {
+            let first = (0..first_len)
+                .map(|_| rng.gen::<f64>())
+                .collect::<Vec<f64>>();
+            let second = (0..second_len)
+                .map(|_| rng.gen::<f64>())
+                .collect::<Vec<f64>>();
+            let reversed = second.iter().rev().cloned().collect::<Vec<f64>>();
+            let full_convolution = naive_convolve(&first, &second);
+            let full_correlation = naive_convolve(&first, &reversed);
+            let same_start = (second_len - 1) / 2;
+            let valid_start = first_len.min(second_len) - 1;
+            let valid_len = first_len.max(second_len) - first_len.min(second_len) + 1;
+            for &(mode, start, len) in [
+                (ConvolutionMode::Full, 0, first_len + second_len - 1),
+                (ConvolutionMode::Same, same_start, first_len),
+                (ConvolutionMode::Valid, valid_start, valid_len),
+            ]
+            .iter()
+            {
+                let convolution = convolver.convolve(&first, &second, mode).unwrap();
+                let correlation = convolver.correlate(&first, &second, mode).unwrap();
+                assert_eq!(convolution.len(), len);
+                assert_eq!(correlation.len(), len);
+                for (n, (conv, corr)) in convolution.iter().zip(correlation.iter()).enumerate() {
+                    let conv_diff = (conv - full_convolution[start + n]).abs();
+                    let corr_diff = (corr - full_correlation[start + n]).abs();
+                    assert!(
+                        conv_diff < 1.0e-9 && corr_diff < 1.0e-9,
+                        "Lengths: {} {}, mode: {:?}, too large error: {} {}",
+                        first_len,
+                        second_len,
+                        mode,
+                        conv_diff,
+                        corr_diff
+                    );
+                }
+            }
+        }
+        assert!(convolver
+            .convolve(&[], &[1.0], ConvolutionMode::Full)
+            .unwrap()
+            .is_empty());
+        // Non-finite values are reported as errors
+        assert!(matches!(
+            convolver.convolve(&[1.0, f64::NAN], &[1.0, 2.0], ConvolutionMode::Full),
+            Err(FftError::InputValues(..))
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::is_smooth;
+use crate::{FftError, RealFftPlanner, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::{Fft, FftNum};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// A zoom FFT, that evaluates the spectrum of a real-valued signal at `M` equally spaced frequencies
+/// between a start and an end frequency.
+///
+/// The frequencies are given as fractions of the sample rate, and both the start and end frequencies are included.
+/// The value at frequency `f` is `sum(x[n] * exp(-2*pi*i*f*n))`, with the same scaling as the FFT.
+///
+/// This is a chirp-Z transform along the unit circle, calculated with Bluestein's algorithm.
+/// The sum is rewritten as a convolution with a chirp, which is calculated using complex FFTs
+/// of the shortest length of at least `N + M - 1` that only has the prime factors 2, 3 and 5,
+/// planned by the planner of the RealFftPlanner.
+pub struct ZoomFft<T> {
+    input_len: usize,
+    bins: usize,
+    start: f64,
+    step: f64,
+    pre_chirp: Vec<Complex<T>>,
+    chirp_spectrum: Vec<Complex<T>>,
+    post_chirp: Vec<Complex<T>>,
+    fft: Arc<dyn Fft<T>>,
+    ifft: Arc<dyn Fft<T>>,
+    scratch_len: usize,
+}
+
+// Calculate `exp(-2*pi*i*cycles)`, reducing the number of cycles first to keep the precision for large arguments.
+fn chirp<T: FftNum>(cycles: f64) -> Complex<T> {
+    let angle = -2.0 * PI * (cycles - cycles.floor());
+    Complex {
+        re: T::from_f64(angle.cos()).unwrap(),
+        im: T::from_f64(angle.sin()).unwrap(),
+    }
+}
+
+impl<T: FftNum> ZoomFft<T> {
+    /// Create a new zoom FFT for signals of length `input_len`, that evaluates `bins` frequencies from `start` to `end`.
+    /// The frequencies are fractions of the sample rate, so that for example `0.5` is the Nyquist frequency.
+    /// Uses the complex planner of the given RealFftPlanner to build the FFTs for the convolution.
+    /// Panics if the input length or the number of bins is zero.
+    pub fn new(
+        input_len: usize,
+        bins: usize,
+        start: f64,
+        end: f64,
+        planner: &mut RealFftPlanner<T>,
+    ) -> Self {
+        if input_len == 0 || bins == 0 {
+            panic!("The input length and the number of bins must be at least 1");
+        }
+        let step = if bins > 1 {
+            (end - start) / (bins - 1) as f64
+        } else {
+            0.0
+        };
+        // The convolution uses complex FFTs, so the length doesn't need to be even.
+        let mut fft_len = input_len + bins - 1;
+        while !is_smooth(fft_len) {
+            fft_len += 1;
+        }
+
+        // Using `n*k = (n^2 + k^2 - (k-n)^2)/2`, the signal is multiplied by a chirp,
+        // convolved with a chirp, and the result is multiplied by a chirp.
+        let pre_chirp = (0..input_len)
+            .map(|n| {
+                let n = n as f64;
+                chirp(start * n + 0.5 * step * n * n)
+            })
+            .collect();
+        let post_chirp = (0..bins)
+            .map(|k| {
+                let k = k as f64;
+                chirp(0.5 * step * k * k)
+            })
+            .collect();
+
+        // Store the chirp for both positive and negative offsets, and include the iFFT scaling in its spectrum.
+        let scale = T::one() / T::from_usize(fft_len).unwrap();
+        let mut chirp_spectrum = vec![Complex::zero(); fft_len];
+        for m in 0..bins.max(input_len) {
+            let value = chirp::<T>(-0.5 * step * (m * m) as f64) * scale;
+            if m < bins {
+                chirp_spectrum[m] = value;
+            }
+            if m > 0 && m < input_len {
+                chirp_spectrum[fft_len - m] = value;
+            }
+        }
+        let fft = planner.complex_planner().plan_fft_forward(fft_len);
+        let ifft = planner.complex_planner().plan_fft_inverse(fft_len);
+        fft.process(&mut chirp_spectrum);
+        let scratch_len = fft_len
+            + fft
+                .get_inplace_scratch_len()
+                .max(ifft.get_inplace_scratch_len());
+        ZoomFft {
+            input_len,
+            bins,
+            start,
+            step,
+            pre_chirp,
+            chirp_spectrum,
+            post_chirp,
+            fft,
+            ifft,
+            scratch_len,
+        }
+    }
+
+    /// Evaluate the spectrum of the input, storing the result in the output.
+    /// It allocates scratch space as needed.
+    /// An error is returned if the input or output has the wrong length.
+    pub fn process(&self, input: &[T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Evaluate the spectrum of the input, storing the result in the output.
+    /// It uses the provided scratch vector instead of allocating, which makes this the faster of the two.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &[T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.input_len {
+            return Err(FftError::InputBuffer(self.input_len, input.len()));
+        }
+        if output.len() != self.bins {
+            return Err(FftError::OutputBuffer(self.bins, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let fft_len = self.chirp_spectrum.len();
+        let (buffer, fft_scratch) = scratch.split_at_mut(fft_len);
+        for (buf, (x, pre)) in buffer
+            .iter_mut()
+            .zip(input.iter().zip(self.pre_chirp.iter()))
+        {
+            *buf = *pre * *x;
+        }
+        for buf in buffer[self.input_len..].iter_mut() {
+            *buf = Complex::zero();
+        }
+        self.fft.process_with_scratch(
+            buffer,
+            &mut fft_scratch[..self.fft.get_inplace_scratch_len()],
+        );
+        for (buf, chirp) in buffer.iter_mut().zip(self.chirp_spectrum.iter()) {
+            *buf = *buf * *chirp;
+        }
+        self.ifft.process_with_scratch(
+            buffer,
+            &mut fft_scratch[..self.ifft.get_inplace_scratch_len()],
+        );
+        for (out, (buf, post)) in output
+            .iter_mut()
+            .zip(buffer.iter().zip(self.post_chirp.iter()))
+        {
+            *out = *buf * *post;
+        }
+        Ok(())
+    }
+
+    /// Get the frequencies of the bins, for the given sample rate.
+    pub fn frequencies(&self, sample_rate: T) -> Vec<T> {
+        (0..self.bins)
+            .map(|k| T::from_f64(self.start + self.step * k as f64).unwrap() * sample_rate)
+            .collect()
+    }
+
+    /// Get the length of the input signals.
+    pub fn input_len(&self) -> usize {
+        self.input_len
+    }
+
+    /// Get the number of frequencies that are evaluated.
+    pub fn bins(&self) -> usize {
+        self.bins
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.input_len]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.bins]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FftError, RealFftPlanner, ZoomFft};
+    use rand::Rng;
+    use rustfft::num_complex::Complex;
+    use std::f64::consts::PI;
+
+    // Compare with the DFT sum evaluated directly at each frequency
+    #[test]
+    fn zoom_fft() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut rng = rand::thread_rng();
+        for &(input_len, bins, start, end) in [
+            (37, 20, 0.1, 0.15),
+            (64, 101, 0.0, 0.5),
+            (10, 3, 0.45, 0.3),
+            (5, 1, 0.2, 0.4),
+        ]
+        .iter()
+        {
+            let zoom = ZoomFft::new(input_len, bins, start, end, &mut planner);
+            let input: Vec<f64> = (0..input_len).map(|_| rng.gen::<f64>()).collect();
+            let mut output = zoom.make_output_vec();
+            zoom.process(&input, &mut output).unwrap();
+            let freqs = zoom.frequencies(1.0);
+            assert!((freqs[0] - start).abs() < 1.0e-12);
+            if bins > 1 {
+                assert!((freqs[bins - 1] - end).abs() < 1.0e-12);
+            }
+            for (val, f) in output.iter().zip(freqs.iter()) {
+                let expected: Complex<f64> = input
+                    .iter()
+                    .enumerate()
+                    .map(|(n, x)| Complex::from_polar(*x, -2.0 * PI * f * n as f64))
+                    .sum();
+                assert!((val - expected).norm() < 1.0e-9, "{} {}", input_len, f);
+            }
+        }
+
+        let zoom = ZoomFft::new(8, 4, 0.0, 0.1, &mut planner);
+        let mut output = zoom.make_output_vec();
+        let res = zoom.process(&[0.0; 7], &mut output);
+        assert!(matches!(res, Err(FftError::InputBuffer(8, 7))));
+    }
+}
@@ -0,0 +1,539 @@
+use crate::{
+    compute_twiddle, ComplexToReal, FftError, RealFftPlanner, RealToComplex, RealToReal, Res,
+};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A DCT-II, defined as `X[k] = sum(x[n] * cos(pi * (n + 1/2) * k / N))` for `n` in `0..N`.
+///
+/// The input is reordered to put the even-indexed values first, followed by the odd-indexed ones in reverse.
+/// This is transformed with a real-to-complex FFT of the same length, and each value of the spectrum
+/// is then rotated by a twiddle factor to give two values of the result.
+/// The output is not normalized, see `Dct3` for the inverse transform.
+pub struct Dct2<T> {
+    length: usize,
+    fft: Arc<dyn RealToComplex<T>>,
+    twiddles: Vec<Complex<T>>,
+    scratch_len: usize,
+}
+
+/// A DCT-III, defined as `x[n] = X[0]/2 + sum(X[k] * cos(pi * (n + 1/2) * k / N))` for `k` in `1..N`.
+///
+/// This is the inverse of the DCT-II, apart from scaling. Transforming a vector with a DCT-II
+/// followed by a DCT-III gives the original vector multiplied by `N/2`.
+/// It runs the steps of the DCT-II backwards, using a complex-to-real iFFT of the same length.
+pub struct Dct3<T> {
+    length: usize,
+    fft: Arc<dyn ComplexToReal<T>>,
+    twiddles: Vec<Complex<T>>,
+    scratch_len: usize,
+}
+
+/// A DCT-IV, defined as `X[k] = sum(x[n] * cos(pi * (n + 1/2) * (k + 1/2) / N))` for `n` in `0..N`.
+///
+/// The DCT-IV is its own inverse, apart from scaling. Transforming a vector twice gives the original vector multiplied by `N/2`.
+///
+/// For even lengths, the even-indexed values and the odd-indexed values in reverse are packed into the real and
+/// imaginary parts of N/2 complex values, which are transformed using a complex FFT of length N/2.
+/// Odd lengths are transformed using a zero-padded complex FFT of length 2*N.
+pub struct Dct4<T> {
+    length: usize,
+    fft: Arc<dyn rustfft::Fft<T>>,
+    pre_twiddles: Vec<Complex<T>>,
+    post_twiddles: Vec<Complex<T>>,
+    scratch_len: usize,
+}
+
+/// A planner is used to create DCTs. It caches results internally,
+/// so when making more than one DCT it is advisable to reuse the same planner.
+pub struct DctPlanner<T: FftNum> {
+    planner: RealFftPlanner<T>,
+    dct2_cache: HashMap<usize, Arc<dyn RealToReal<T>>>,
+    dct3_cache: HashMap<usize, Arc<dyn RealToReal<T>>>,
+    dct4_cache: HashMap<usize, Arc<dyn RealToReal<T>>>,
+}
+
+impl<T: FftNum> DctPlanner<T> {
+    /// Create a new planner.
+    pub fn new() -> Self {
+        Self {
+            planner: RealFftPlanner::new(),
+            dct2_cache: HashMap::new(),
+            dct3_cache: HashMap::new(),
+            dct4_cache: HashMap::new(),
+        }
+    }
+
+    /// Plan a DCT-II. Returns the DCT in a shared reference.
+    /// If requesting a second DCT of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dct2(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dct) = self.dct2_cache.get(&len) {
+            Arc::clone(dct)
+        } else {
+            let dct = Arc::new(Dct2::new(len, &mut self.planner)) as Arc<dyn RealToReal<T>>;
+            self.dct2_cache.insert(len, Arc::clone(&dct));
+            dct
+        }
+    }
+
+    /// Plan a DCT-III. Returns the DCT in a shared reference.
+    /// If requesting a second DCT of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dct3(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dct) = self.dct3_cache.get(&len) {
+            Arc::clone(dct)
+        } else {
+            let dct = Arc::new(Dct3::new(len, &mut self.planner)) as Arc<dyn RealToReal<T>>;
+            self.dct3_cache.insert(len, Arc::clone(&dct));
+            dct
+        }
+    }
+
+    /// Plan a DCT-IV. Returns the DCT in a shared reference.
+    /// If requesting a second DCT of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dct4(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dct) = self.dct4_cache.get(&len) {
+            Arc::clone(dct)
+        } else {
+            let dct = Arc::new(Dct4::new(len, &mut self.planner)) as Arc<dyn RealToReal<T>>;
+            self.dct4_cache.insert(len, Arc::clone(&dct));
+            dct
+        }
+    }
+}
+
+impl<T: FftNum> Default for DctPlanner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: FftNum> Dct2<T> {
+    /// Create a new DCT-II for input data of a given length, and uses the given RealFftPlanner to build the inner FFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let fft = planner.plan_fft_forward(length);
+        let twiddles = (0..length / 2 + 1)
+            .map(|k| compute_twiddle(k, 4 * length))
+            .collect();
+        let scratch_len = length / 2 + 1 + fft.get_scratch_len();
+        Dct2 {
+            length,
+            fft,
+            twiddles,
+            scratch_len,
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dct2<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        if self.length == 0 {
+            return Ok(());
+        }
+        // Even values in order, followed by the odd values in reverse
+        let (evens, odds) = output.split_at_mut((self.length + 1) / 2);
+        for (out, val) in evens.iter_mut().zip(input.iter().step_by(2)) {
+            *out = *val;
+        }
+        for (out, val) in odds.iter_mut().rev().zip(input.iter().skip(1).step_by(2)) {
+            *out = *val;
+        }
+
+        let (spectrum, fft_scratch) = scratch.split_at_mut(self.length / 2 + 1);
+        self.fft
+            .process_with_scratch(output, spectrum, fft_scratch)?;
+
+        // Each twiddled value gives the result at k as the real part, and at N-k as the negated imaginary part
+        output[0] = (spectrum[0] * self.twiddles[0]).re;
+        for (k, (value, twiddle)) in spectrum
+            .iter()
+            .zip(self.twiddles.iter())
+            .enumerate()
+            .skip(1)
+        {
+            let rotated = *value * twiddle;
+            output[self.length - k] = -rotated.im;
+            output[k] = rotated.re;
+        }
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> Dct3<T> {
+    /// Create a new DCT-III for input data of a given length, and uses the given RealFftPlanner to build the inner iFFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let fft = planner.plan_fft_inverse(length);
+        let half = T::from_f64(0.5).unwrap();
+        let twiddles = (0..length / 2 + 1)
+            .map(|k| compute_twiddle::<T>(k, 4 * length).conj() * half)
+            .collect();
+        let scratch_len = length / 2 + 1 + fft.get_scratch_len();
+        Dct3 {
+            length,
+            fft,
+            twiddles,
+            scratch_len,
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dct3<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        if self.length == 0 {
+            return Ok(());
+        }
+        let (spectrum, fft_scratch) = scratch.split_at_mut(self.length / 2 + 1);
+        spectrum[0] = Complex::from(input[0]) * self.twiddles[0];
+        for (k, (value, twiddle)) in spectrum
+            .iter_mut()
+            .zip(self.twiddles.iter())
+            .enumerate()
+            .skip(1)
+        {
+            *value = Complex::new(input[k], -input[self.length - k]) * twiddle;
+        }
+        // These must be real, clear any rounding errors
+        spectrum[0].im = T::zero();
+        if self.length % 2 == 0 {
+            spectrum[self.length / 2].im = T::zero();
+        }
+        self.fft
+            .process_with_scratch(spectrum, input, fft_scratch)?;
+
+        // Undo the reordering of the DCT-II
+        for (out, val) in output.iter_mut().step_by(2).zip(input.iter()) {
+            *out = *val;
+        }
+        for (out, val) in output.iter_mut().skip(1).step_by(2).zip(input.iter().rev()) {
+            *out = *val;
+        }
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> Dct4<T> {
+    /// Create a new DCT-IV for input data of a given length, and uses the given RealFftPlanner to build the inner FFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let (fft, pre_twiddles, post_twiddles) = if length % 2 == 0 {
+            let fft = planner.complex_planner().plan_fft_forward(length / 2);
+            let pre_twiddles = (0..length / 2)
+                .map(|n| compute_twiddle(4 * n + 1, 8 * length))
+                .collect();
+            let post_twiddles = (0..length / 2)
+                .map(|k| compute_twiddle(k, 2 * length))
+                .collect();
+            (fft, pre_twiddles, post_twiddles)
+        } else {
+            let fft = planner.complex_planner().plan_fft_forward(2 * length);
+            let pre_twiddles = (0..length)
+                .map(|n| compute_twiddle(n, 4 * length))
+                .collect();
+            let post_twiddles = (0..length)
+                .map(|k| compute_twiddle(2 * k + 1, 8 * length))
+                .collect();
+            (fft, pre_twiddles, post_twiddles)
+        };
+        let scratch_len = fft.len() + fft.get_inplace_scratch_len();
+        Dct4 {
+            length,
+            fft,
+            pre_twiddles,
+            post_twiddles,
+            scratch_len,
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dct4<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let (buffer, fft_scratch) = scratch.split_at_mut(self.fft.len());
+        if self.length % 2 == 0 {
+            for (n, (value, twiddle)) in buffer.iter_mut().zip(self.pre_twiddles.iter()).enumerate()
+            {
+                *value = Complex::new(input[2 * n], input[self.length - 1 - 2 * n]) * twiddle;
+            }
+            self.fft.process_with_scratch(buffer, fft_scratch);
+            for (k, (value, twiddle)) in buffer.iter().zip(self.post_twiddles.iter()).enumerate() {
+                let rotated = *value * twiddle;
+                output[2 * k] = rotated.re;
+                output[self.length - 1 - 2 * k] = -rotated.im;
+            }
+        } else {
+            let (values, padding) = buffer.split_at_mut(self.length);
+            for (value, (x, twiddle)) in values
+                .iter_mut()
+                .zip(input.iter().zip(self.pre_twiddles.iter()))
+            {
+                *value = twiddle * *x;
+            }
+            for value in padding.iter_mut() {
+                *value = Complex::zero();
+            }
+            self.fft.process_with_scratch(buffer, fft_scratch);
+            for (out, (value, twiddle)) in output
+                .iter_mut()
+                .zip(buffer.iter().zip(self.post_twiddles.iter()))
+            {
+                *out = (*value * twiddle).re;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DctPlanner, FftError};
+    use rand::Rng;
+
+    fn naive_dct2(input: &[f64]) -> Vec<f64> {
+        let len = input.len() as f64;
+        (0..input.len())
+            .map(|k| {
+                input
+                    .iter()
+                    .enumerate()
+                    .map(|(n, x)| {
+                        x * (std::f64::consts::PI * (n as f64 + 0.5) * k as f64 / len).cos()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn naive_dct3(input: &[f64]) -> Vec<f64> {
+        let len = input.len() as f64;
+        (0..input.len())
+            .map(|n| {
+                input[0] / 2.0
+                    + input
+                        .iter()
+                        .enumerate()
+                        .skip(1)
+                        .map(|(k, x)| {
+                            x * (std::f64::consts::PI * (n as f64 + 0.5) * k as f64 / len).cos()
+                        })
+                        .sum::<f64>()
+            })
+            .collect()
+    }
+
+    // Compare DCT-II and DCT-III with the direct sums
+    #[test]
+    fn dct2_dct3() {
+        let mut planner = DctPlanner::<f64>::new();
+        for length in 1..40 {
+            let dct2 = planner.plan_dct2(length);
+            let dct3 = planner.plan_dct3(length);
+            let mut rng = rand::thread_rng();
+            let mut indata = dct2.make_input_vec();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let expected2 = naive_dct2(&indata);
+            let expected3 = naive_dct3(&indata);
+            let mut input2 = indata.clone();
+            let mut output2 = dct2.make_output_vec();
+            dct2.process(&mut input2, &mut output2).unwrap();
+            let mut input3 = indata.clone();
+            let mut output3 = dct3.make_output_vec();
+            dct3.process(&mut input3, &mut output3).unwrap();
+            for (expected, output) in [(&expected2, &output2), (&expected3, &output3)].iter() {
+                for (e, o) in expected.iter().zip(output.iter()) {
+                    assert!(
+                        (e - o).abs() < 1.0e-9,
+                        "Length: {}, too large error: {}",
+                        length,
+                        (e - o).abs()
+                    );
+                }
+            }
+        }
+    }
+
+    // Compare DCT-IV with the direct sum, and check that it is its own inverse
+    #[test]
+    fn dct4() {
+        let mut planner = DctPlanner::<f64>::new();
+        for length in 1..40 {
+            let dct4 = planner.plan_dct4(length);
+            let mut rng = rand::thread_rng();
+            let mut indata = dct4.make_input_vec();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let original = indata.clone();
+            let len = length as f64;
+            let expected = (0..length)
+                .map(|k| {
+                    indata
+                        .iter()
+                        .enumerate()
+                        .map(|(n, x)| {
+                            x * (std::f64::consts::PI * (n as f64 + 0.5) * (k as f64 + 0.5) / len)
+                                .cos()
+                        })
+                        .sum::<f64>()
+                })
+                .collect::<Vec<f64>>();
+            let mut output = dct4.make_output_vec();
+            dct4.process(&mut indata, &mut output).unwrap();
+            for (e, o) in expected.iter().zip(output.iter()) {
+                assert!(
+                    (e - o).abs() < 1.0e-9,
+                    "Length: {}, too large error: {}",
+                    length,
+                    (e - o).abs()
+                );
+            }
+            let mut result = dct4.make_output_vec();
+            dct4.process(&mut output, &mut result).unwrap();
+            for (orig, val) in original.iter().zip(result.iter()) {
+                let diff = (orig - val * 2.0 / len).abs();
+                assert!(
+                    diff < 1.0e-9,
+                    "Length: {}, too large error: {}",
+                    length,
+                    diff
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dct_errors() {
+        let mut planner = DctPlanner::<f64>::new();
+        let dct2 = planner.plan_dct2(8);
+        let mut input = vec![0.0; 7];
+        let mut output = vec![0.0; 8];
+        let res = dct2.process(&mut input, &mut output);
+        assert!(matches!(res, Err(FftError::InputBuffer(8, 7))));
+        let mut input = vec![0.0; 8];
+        let mut output = vec![0.0; 9];
+        let res = dct2.process(&mut input, &mut output);
+        assert!(matches!(res, Err(FftError::OutputBuffer(8, 9))));
+        let mut output = vec![0.0; 8];
+        let mut scratch = vec![];
+        let res = dct2.process_with_scratch(&mut input, &mut output, &mut scratch);
+        assert!(matches!(res, Err(FftError::ScratchBuffer(_, 0))));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{FftError, RealFftPlanner, RealToComplex, RealToReal, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::sync::Arc;
+
+/// A Discrete Hartley Transform, defined as `H[k] = sum(x[n] * (cos(2*pi*n*k/N) + sin(2*pi*n*k/N)))` for `n` in `0..N`.
+///
+/// This equals `Re(X[k]) - Im(X[k])`, where `X` is the FFT of the input.
+/// The input is transformed with a real-to-complex FFT, and the values of the second half of the result
+/// are taken from the complex conjugates of the first half.
+///
+/// The DHT is its own inverse, apart from scaling. Transforming a vector twice gives the original vector multiplied by `N`.
+pub struct Dht<T> {
+    length: usize,
+    fft: Arc<dyn RealToComplex<T>>,
+    scratch_len: usize,
+}
+
+impl<T: FftNum> Dht<T> {
+    /// Create a new DHT for input data of a given length, and uses the given RealFftPlanner to build the inner FFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let fft = planner.plan_fft_forward(length);
+        let scratch_len = length / 2 + 1 + fft.get_scratch_len();
+        Dht {
+            length,
+            fft,
+            scratch_len,
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dht<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        if self.length == 0 {
+            return Ok(());
+        }
+        let (spectrum, fft_scratch) = scratch.split_at_mut(self.length / 2 + 1);
+        self.fft
+            .process_with_scratch(input, spectrum, fft_scratch)?;
+        output[0] = spectrum[0].re - spectrum[0].im;
+        // The value at N-k is the complex conjugate of the value at k
+        for (k, value) in spectrum.iter().enumerate().skip(1) {
+            output[self.length - k] = value.re + value.im;
+            output[k] = value.re - value.im;
+        }
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RealFftPlanner;
+    use rand::Rng;
+    use std::f64::consts::PI;
+
+    // Compare with the direct sum, and check that the transform is its own inverse
+    #[test]
+    fn dht() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        for length in 1..40 {
+            let dht = planner.plan_dht(length);
+            let mut rng = rand::thread_rng();
+            let mut indata = dht.make_input_vec();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let original = indata.clone();
+            let expected = (0..length)
+                .map(|k| {
+                    indata
+                        .iter()
+                        .enumerate()
+                        .map(|(n, x)| {
+                            let angle = 2.0 * PI * (n * k) as f64 / length as f64;
+                            x * (angle.cos() + angle.sin())
+                        })
+                        .sum::<f64>()
+                })
+                .collect::<Vec<f64>>();
+            let mut output = dht.make_output_vec();
+            dht.process(&mut indata, &mut output).unwrap();
+            for (e, o) in expected.iter().zip(output.iter()) {
+                assert!(
+                    (e - o).abs() < 1.0e-9,
+                    "Length: {}, too large error: {}",
+                    length,
+                    (e - o).abs()
+                );
+            }
+            let mut result = dht.make_output_vec();
+            dht.process(&mut output, &mut result).unwrap();
+            for (orig, val) in original.iter().zip(result.iter()) {
+                let diff = (orig - val / length as f64).abs();
+                assert!(
+                    diff < 1.0e-9,
+                    "Length: {}, too large error: {}",
+                    length,
+                    diff
+                );
+            }
+        }
+    }
+}
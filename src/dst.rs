@@ -0,0 +1,450 @@
+use crate::{as_real, Dct2, Dct3, Dct4, FftError, RealFftPlanner, RealToComplex, RealToReal, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A DST-I, defined as `X[k] = sum(x[n] * sin(pi * (n + 1) * (k + 1) / (N + 1)))` for `n` in `0..N`.
+///
+/// The DST-I is its own inverse, apart from scaling. Transforming a vector twice gives the original vector multiplied by `(N+1)/2`.
+/// The input is extended to an odd-symmetric sequence of length 2*(N+1), which is transformed with a real-to-complex FFT.
+pub struct Dst1<T> {
+    length: usize,
+    fft: Arc<dyn RealToComplex<T>>,
+    scratch_len: usize,
+}
+
+/// A DST-II, defined as `X[k] = sum(x[n] * sin(pi * (n + 1/2) * (k + 1) / N))` for `n` in `0..N`.
+///
+/// It is calculated as a DCT-II of the input with every odd value negated, with the output in reverse order.
+/// The output is not normalized, see `Dst3` for the inverse transform.
+pub struct Dst2<T> {
+    dct: Dct2<T>,
+}
+
+/// A DST-III, defined as `x[n] = (-1)^n * X[N-1]/2 + sum(X[k] * sin(pi * (n + 1/2) * (k + 1) / N))` for `k` in `0..N-1`.
+///
+/// This is the inverse of the DST-II, apart from scaling. Transforming a vector with a DST-II
+/// followed by a DST-III gives the original vector multiplied by `N/2`.
+/// It is calculated as a DCT-III of the reversed input, with every odd value of the output negated.
+pub struct Dst3<T> {
+    dct: Dct3<T>,
+}
+
+/// A DST-IV, defined as `X[k] = sum(x[n] * sin(pi * (n + 1/2) * (k + 1/2) / N))` for `n` in `0..N`.
+///
+/// The DST-IV is its own inverse, apart from scaling. Transforming a vector twice gives the original vector multiplied by `N/2`.
+/// It is calculated as a DCT-IV of the reversed input, with every odd value of the output negated.
+pub struct Dst4<T> {
+    dct: Dct4<T>,
+}
+
+/// A planner is used to create DSTs. It caches results internally,
+/// so when making more than one DST it is advisable to reuse the same planner.
+pub struct DstPlanner<T: FftNum> {
+    planner: RealFftPlanner<T>,
+    dst1_cache: HashMap<usize, Arc<dyn RealToReal<T>>>,
+    dst2_cache: HashMap<usize, Arc<dyn RealToReal<T>>>,
+    dst3_cache: HashMap<usize, Arc<dyn RealToReal<T>>>,
+    dst4_cache: HashMap<usize, Arc<dyn RealToReal<T>>>,
+}
+
+impl<T: FftNum> DstPlanner<T> {
+    /// Create a new planner.
+    pub fn new() -> Self {
+        Self {
+            planner: RealFftPlanner::new(),
+            dst1_cache: HashMap::new(),
+            dst2_cache: HashMap::new(),
+            dst3_cache: HashMap::new(),
+            dst4_cache: HashMap::new(),
+        }
+    }
+
+    /// Plan a DST-I. Returns the DST in a shared reference.
+    /// If requesting a second DST of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dst1(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dst) = self.dst1_cache.get(&len) {
+            Arc::clone(dst)
+        } else {
+            let dst = Arc::new(Dst1::new(len, &mut self.planner)) as Arc<dyn RealToReal<T>>;
+            self.dst1_cache.insert(len, Arc::clone(&dst));
+            dst
+        }
+    }
+
+    /// Plan a DST-II. Returns the DST in a shared reference.
+    /// If requesting a second DST of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dst2(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dst) = self.dst2_cache.get(&len) {
+            Arc::clone(dst)
+        } else {
+            let dst = Arc::new(Dst2::new(len, &mut self.planner)) as Arc<dyn RealToReal<T>>;
+            self.dst2_cache.insert(len, Arc::clone(&dst));
+            dst
+        }
+    }
+
+    /// Plan a DST-III. Returns the DST in a shared reference.
+    /// If requesting a second DST of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dst3(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dst) = self.dst3_cache.get(&len) {
+            Arc::clone(dst)
+        } else {
+            let dst = Arc::new(Dst3::new(len, &mut self.planner)) as Arc<dyn RealToReal<T>>;
+            self.dst3_cache.insert(len, Arc::clone(&dst));
+            dst
+        }
+    }
+
+    /// Plan a DST-IV. Returns the DST in a shared reference.
+    /// If requesting a second DST of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dst4(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dst) = self.dst4_cache.get(&len) {
+            Arc::clone(dst)
+        } else {
+            let dst = Arc::new(Dst4::new(len, &mut self.planner)) as Arc<dyn RealToReal<T>>;
+            self.dst4_cache.insert(len, Arc::clone(&dst));
+            dst
+        }
+    }
+}
+
+impl<T: FftNum> Default for DstPlanner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Check the buffer lengths, before the input is modified.
+fn check_lengths<T>(
+    length: usize,
+    input: &[T],
+    output: &[T],
+    scratch_len: usize,
+    scratch: &[Complex<T>],
+) -> Res<()> {
+    if input.len() != length {
+        return Err(FftError::InputBuffer(length, input.len()));
+    }
+    if output.len() != length {
+        return Err(FftError::OutputBuffer(length, output.len()));
+    }
+    if scratch.len() < scratch_len {
+        return Err(FftError::ScratchBuffer(scratch_len, scratch.len()));
+    }
+    Ok(())
+}
+
+// Negate every odd value.
+fn negate_odd<T: FftNum>(values: &mut [T]) {
+    for val in values.iter_mut().skip(1).step_by(2) {
+        *val = -*val;
+    }
+}
+
+impl<T: FftNum> Dst1<T> {
+    /// Create a new DST-I for input data of a given length, and uses the given RealFftPlanner to build the inner FFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let fft = planner.plan_fft_forward(2 * (length + 1));
+        let scratch_len = (length + 1) + (length + 2) + fft.get_scratch_len();
+        Dst1 {
+            length,
+            fft,
+            scratch_len,
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dst1<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        check_lengths(self.length, input, output, self.scratch_len, scratch)?;
+        let (extended, rest) = scratch.split_at_mut(self.length + 1);
+        let (spectrum, fft_scratch) = rest.split_at_mut(self.length + 2);
+        let extended = as_real(extended);
+
+        // Extend to [0, x, 0, -x_rev]
+        let (first, second) = extended.split_at_mut(self.length + 1);
+        first[0] = T::zero();
+        second[0] = T::zero();
+        for (val, (a, b)) in input
+            .iter()
+            .zip(first[1..].iter_mut().zip(second[1..].iter_mut().rev()))
+        {
+            *a = *val;
+            *b = -*val;
+        }
+        self.fft
+            .process_with_scratch(extended, spectrum, fft_scratch)?;
+        let half = T::from_f64(0.5).unwrap();
+        for (out, val) in output.iter_mut().zip(spectrum[1..].iter()) {
+            *out = -val.im * half;
+        }
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> Dst2<T> {
+    /// Create a new DST-II for input data of a given length, and uses the given RealFftPlanner to build the inner FFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        Dst2 {
+            dct: Dct2::new(length, planner),
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dst2<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        check_lengths(self.len(), input, output, self.get_scratch_len(), scratch)?;
+        negate_odd(input);
+        self.dct.process_with_scratch(input, output, scratch)?;
+        output.reverse();
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.dct.get_scratch_len()
+    }
+
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> Dst3<T> {
+    /// Create a new DST-III for input data of a given length, and uses the given RealFftPlanner to build the inner iFFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        Dst3 {
+            dct: Dct3::new(length, planner),
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dst3<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        check_lengths(self.len(), input, output, self.get_scratch_len(), scratch)?;
+        input.reverse();
+        self.dct.process_with_scratch(input, output, scratch)?;
+        negate_odd(output);
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.dct.get_scratch_len()
+    }
+
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> Dst4<T> {
+    /// Create a new DST-IV for input data of a given length, and uses the given RealFftPlanner to build the inner FFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        Dst4 {
+            dct: Dct4::new(length, planner),
+        }
+    }
+}
+
+impl<T: FftNum> RealToReal<T> for Dst4<T> {
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        check_lengths(self.len(), input, output, self.get_scratch_len(), scratch)?;
+        input.reverse();
+        self.dct.process_with_scratch(input, output, scratch)?;
+        negate_odd(output);
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.dct.get_scratch_len()
+    }
+
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DstPlanner, FftError};
+    use rand::Rng;
+    use std::f64::consts::PI;
+
+    // Direct sum of a DST, with the given offsets added to n and k, and the given period.
+    fn naive_dst(input: &[f64], n_offset: f64, k_offset: f64, period: f64) -> Vec<f64> {
+        (0..input.len())
+            .map(|k| {
+                input
+                    .iter()
+                    .enumerate()
+                    .map(|(n, x)| {
+                        x * (PI * (n as f64 + n_offset) * (k as f64 + k_offset) / period).sin()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    // Compare all DST types with the direct sums
+    #[test]
+    fn dst() {
+        let mut planner = DstPlanner::<f64>::new();
+        for length in 1..40 {
+            let len = length as f64;
+            let mut rng = rand::thread_rng();
+            let indata = (0..length).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+            // The DST-III is a DST-II in the other direction, with the last value halved
+            let mut halved = indata.clone();
+            halved[length - 1] *= 0.5;
+            let expected = [
+                naive_dst(&indata, 1.0, 1.0, len + 1.0),
+                naive_dst(&indata, 0.5, 1.0, len),
+                (0..length)
+                    .map(|n| {
+                        halved
+                            .iter()
+                            .enumerate()
+                            .map(|(k, x)| {
+                                x * (PI * (n as f64 + 0.5) * (k as f64 + 1.0) / len).sin()
+                            })
+                            .sum()
+                    })
+                    .collect(),
+                naive_dst(&indata, 0.5, 0.5, len),
+            ];
+            let dsts = [
+                planner.plan_dst1(length),
+                planner.plan_dst2(length),
+                planner.plan_dst3(length),
+                planner.plan_dst4(length),
+            ];
+            for (kind, (dst, expected)) in dsts.iter().zip(expected.iter()).enumerate() {
+                let mut input = indata.clone();
+                let mut output = dst.make_output_vec();
+                dst.process(&mut input, &mut output).unwrap();
+                for (e, o) in expected.iter().zip(output.iter()) {
+                    assert!(
+                        (e - o).abs() < 1.0e-9,
+                        "Type: {}, length: {}, too large error: {}",
+                        kind + 1,
+                        length,
+                        (e - o).abs()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dst_errors() {
+        let mut planner = DstPlanner::<f64>::new();
+        for dst in [planner.plan_dst1(8), planner.plan_dst2(8)].iter() {
+            let mut input = vec![0.0; 7];
+            let mut output = vec![0.0; 8];
+            let res = dst.process(&mut input, &mut output);
+            assert!(matches!(res, Err(FftError::InputBuffer(8, 7))));
+            let mut input = vec![0.0; 8];
+            let mut output = vec![0.0; 9];
+            let res = dst.process(&mut input, &mut output);
+            assert!(matches!(res, Err(FftError::OutputBuffer(8, 9))));
+        }
+    }
+}
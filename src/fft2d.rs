@@ -0,0 +1,385 @@
+use crate::{ComplexToReal, FftError, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A two-dimensional FFT that takes a real-valued input of `rows` x `cols` values, and transforms it to
+/// a complex half-spectrum of `rows` x `cols/2+1` values.
+///
+/// Both the input and output are stored row by row.
+/// Each row is first transformed using a real-to-complex FFT, followed by complex FFTs of the columns.
+pub struct RealToComplex2d<T> {
+    rows: usize,
+    cols: usize,
+    row_fft: Arc<dyn RealToComplex<T>>,
+    col_fft: Arc<dyn rustfft::Fft<T>>,
+    scratch_len: usize,
+}
+
+/// A two-dimensional iFFT that takes a complex half-spectrum of `rows` x `cols/2+1` values, and transforms it to
+/// a real-valued result of `rows` x `cols` values.
+///
+/// Both the input and output are stored row by row.
+/// The columns are first transformed using complex iFFTs, followed by complex-to-real iFFTs of the rows.
+pub struct ComplexToReal2d<T> {
+    rows: usize,
+    cols: usize,
+    row_fft: Arc<dyn ComplexToReal<T>>,
+    col_fft: Arc<dyn rustfft::Fft<T>>,
+    scratch_len: usize,
+}
+
+/// A planner is used to create two-dimensional FFTs. It caches results internally,
+/// so when making more than one FFT it is advisable to reuse the same planner.
+pub struct RealFftPlanner2d<T: FftNum> {
+    planner: RealFftPlanner<T>,
+    r2c_cache: HashMap<(usize, usize), Arc<RealToComplex2d<T>>>,
+    c2r_cache: HashMap<(usize, usize), Arc<ComplexToReal2d<T>>>,
+}
+
+impl<T: FftNum> RealFftPlanner2d<T> {
+    /// Create a new planner.
+    pub fn new() -> Self {
+        Self {
+            planner: RealFftPlanner::new(),
+            r2c_cache: HashMap::new(),
+            c2r_cache: HashMap::new(),
+        }
+    }
+
+    /// Plan a two-dimensional Real-to-Complex forward FFT. Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same size, this will return a new reference to the already existing one.
+    pub fn plan_fft_forward(&mut self, rows: usize, cols: usize) -> Arc<RealToComplex2d<T>> {
+        if let Some(fft) = self.r2c_cache.get(&(rows, cols)) {
+            Arc::clone(fft)
+        } else {
+            let fft = Arc::new(RealToComplex2d::new(rows, cols, &mut self.planner));
+            self.r2c_cache.insert((rows, cols), Arc::clone(&fft));
+            fft
+        }
+    }
+
+    /// Plan a two-dimensional Complex-to-Real inverse FFT. Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same size, this will return a new reference to the already existing one.
+    pub fn plan_fft_inverse(&mut self, rows: usize, cols: usize) -> Arc<ComplexToReal2d<T>> {
+        if let Some(fft) = self.c2r_cache.get(&(rows, cols)) {
+            Arc::clone(fft)
+        } else {
+            let fft = Arc::new(ComplexToReal2d::new(rows, cols, &mut self.planner));
+            self.c2r_cache.insert((rows, cols), Arc::clone(&fft));
+            fft
+        }
+    }
+}
+
+impl<T: FftNum> Default for RealFftPlanner2d<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Transpose a matrix of `rows` x `cols` values, stored row by row.
+fn transpose<T: Copy>(input: &[T], output: &mut [T], rows: usize, cols: usize) {
+    for (row_idx, row) in input.chunks_exact(cols).enumerate() {
+        for (col_idx, val) in row.iter().enumerate() {
+            output[col_idx * rows + row_idx] = *val;
+        }
+    }
+}
+
+impl<T: FftNum> RealToComplex2d<T> {
+    /// Create a new two-dimensional RealToComplex FFT for input data of a given size,
+    /// and uses the given RealFftPlanner to build the row and column FFTs.
+    pub fn new(rows: usize, cols: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let row_fft = planner.plan_fft_forward(cols);
+        let col_fft = planner.complex_planner().plan_fft_forward(rows);
+        let scratch_len = rows * (cols / 2 + 1)
+            + row_fft
+                .get_scratch_len()
+                .max(col_fft.get_inplace_scratch_len());
+        RealToComplex2d {
+            rows,
+            cols,
+            row_fft,
+            col_fft,
+            scratch_len,
+        }
+    }
+
+    /// Transform `rows` x `cols` real-valued samples, storing the result in the `rows` x `cols/2+1` element long complex output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process(&self, input: &mut [T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform `rows` x `cols` real-valued samples, storing the result in the `rows` x `cols/2+1` element long complex output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let expected_input_buffer_size = self.rows * self.cols;
+        if input.len() != expected_input_buffer_size {
+            return Err(FftError::InputBuffer(
+                expected_input_buffer_size,
+                input.len(),
+            ));
+        }
+        let bins = self.cols / 2 + 1;
+        let expected_output_buffer_size = self.rows * bins;
+        if output.len() != expected_output_buffer_size {
+            return Err(FftError::OutputBuffer(
+                expected_output_buffer_size,
+                output.len(),
+            ));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        if self.rows == 0 || self.cols == 0 {
+            return Ok(());
+        }
+        let (buffer, fft_scratch) = scratch.split_at_mut(expected_output_buffer_size);
+
+        // Transform the rows, then the columns as rows of the transposed result
+        self.row_fft
+            .process_batch_with_scratch(input, output, fft_scratch)?;
+        transpose(output, buffer, self.rows, bins);
+        self.col_fft.process_with_scratch(buffer, fft_scratch);
+        transpose(buffer, output, bins, self.rows);
+        Ok(())
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Get the number of rows that this FFT can process.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns that this FFT can process.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.rows * self.cols]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.rows * (self.cols / 2 + 1)]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> ComplexToReal2d<T> {
+    /// Create a new two-dimensional ComplexToReal FFT for output data of a given size,
+    /// and uses the given RealFftPlanner to build the row and column FFTs.
+    pub fn new(rows: usize, cols: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let row_fft = planner.plan_fft_inverse(cols);
+        let col_fft = planner.complex_planner().plan_fft_inverse(rows);
+        let scratch_len = rows * (cols / 2 + 1)
+            + row_fft
+                .get_scratch_len()
+                .max(col_fft.get_inplace_scratch_len());
+        ComplexToReal2d {
+            rows,
+            cols,
+            row_fft,
+            col_fft,
+            scratch_len,
+        }
+    }
+
+    /// Transform a complex half-spectrum of `rows` x `cols/2+1` values and store the real result in the `rows` x `cols` long output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// The input is assumed to be the spectrum of real-valued data. The imaginary parts that should be zero after
+    /// the column transforms are ignored, and no error is returned for them.
+    pub fn process(&self, input: &mut [Complex<T>], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a complex half-spectrum of `rows` x `cols/2+1` values and store the real result in the `rows` x `cols` long output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// The input is assumed to be the spectrum of real-valued data. The imaginary parts that should be zero after
+    /// the column transforms are ignored, and no error is returned for them.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [Complex<T>],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let bins = self.cols / 2 + 1;
+        let expected_input_buffer_size = self.rows * bins;
+        if input.len() != expected_input_buffer_size {
+            return Err(FftError::InputBuffer(
+                expected_input_buffer_size,
+                input.len(),
+            ));
+        }
+        let expected_output_buffer_size = self.rows * self.cols;
+        if output.len() != expected_output_buffer_size {
+            return Err(FftError::OutputBuffer(
+                expected_output_buffer_size,
+                output.len(),
+            ));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        if self.rows == 0 || self.cols == 0 {
+            return Ok(());
+        }
+        let (buffer, fft_scratch) = scratch.split_at_mut(expected_input_buffer_size);
+
+        // Transform the columns as rows of the transposed input
+        transpose(input, buffer, self.rows, bins);
+        self.col_fft.process_with_scratch(buffer, fft_scratch);
+        transpose(buffer, input, bins, self.rows);
+
+        // The first, and for even lengths also the last, column now only contain rounding errors
+        // in the imaginary parts. Clear them before transforming the rows.
+        for row in input.chunks_exact_mut(bins) {
+            row[0].im = T::zero();
+            if self.cols % 2 == 0 {
+                row[bins - 1].im = T::zero();
+            }
+        }
+        self.row_fft
+            .process_batch_with_scratch(input, output, fft_scratch)
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Get the number of rows that this FFT can process.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns that this FFT can process.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.rows * (self.cols / 2 + 1)]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.rows * self.cols]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RealFftPlanner2d;
+    use rand::Rng;
+    use rustfft::num_complex::Complex;
+    use rustfft::FftPlanner;
+
+    // Compare with a full two-dimensional complex FFT
+    #[test]
+    fn real_to_complex_2d() {
+        for &(rows, cols) in [(1, 1), (4, 6), (5, 7), (8, 3), (6, 16)].iter() {
+            let mut planner = RealFftPlanner2d::<f64>::new();
+            let r2c = planner.plan_fft_forward(rows, cols);
+            let mut rng = rand::thread_rng();
+            let mut indata = r2c.make_input_vec();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let mut full = indata
+                .iter()
+                .map(Complex::from)
+                .collect::<Vec<Complex<f64>>>();
+            let mut output = r2c.make_output_vec();
+            r2c.process(&mut indata, &mut output).unwrap();
+
+            let mut fft_planner = FftPlanner::<f64>::new();
+            let row_fft = fft_planner.plan_fft_forward(cols);
+            row_fft.process(&mut full);
+            let col_fft = fft_planner.plan_fft_forward(rows);
+            let bins = cols / 2 + 1;
+            for col in 0..bins {
+                let mut column = (0..rows)
+                    .map(|row| full[row * cols + col])
+                    .collect::<Vec<_>>();
+                col_fft.process(&mut column);
+                for row in 0..rows {
+                    let diff = (column[row] - output[row * bins + col]).norm();
+                    assert!(
+                        diff < 1.0e-9,
+                        "Size: {}x{}, too large error: {}",
+                        rows,
+                        cols,
+                        diff
+                    );
+                }
+            }
+        }
+    }
+
+    // Check that a forward and inverse transform gives back the original data, scaled by the number of values
+    #[test]
+    fn complex_to_real_2d() {
+        for &(rows, cols) in [(1, 1), (4, 6), (5, 7), (8, 3), (6, 16)].iter() {
+            let mut planner = RealFftPlanner2d::<f64>::new();
+            let r2c = planner.plan_fft_forward(rows, cols);
+            let c2r = planner.plan_fft_inverse(rows, cols);
+            let mut rng = rand::thread_rng();
+            let mut indata = r2c.make_input_vec();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let original = indata.clone();
+            let mut spectrum = r2c.make_output_vec();
+            let mut output = c2r.make_output_vec();
+            r2c.process(&mut indata, &mut spectrum).unwrap();
+            c2r.process(&mut spectrum, &mut output).unwrap();
+            let scale = (rows * cols) as f64;
+            for (orig, val) in original.iter().zip(output.iter()) {
+                let diff = (orig - val / scale).abs();
+                assert!(
+                    diff < 1.0e-9,
+                    "Size: {}x{}, too large error: {}",
+                    rows,
+                    cols,
+                    diff
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use crate::{as_real, ComplexToReal, FftError, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::sync::Arc;
+
+/// An N-dimensional FFT that takes a real-valued array, and transforms it to a complex half-spectrum.
+///
+/// The real FFT is performed along the last axis, which turns `n` real values into `n/2+1` complex values.
+/// It is followed by complex FFTs along all the other axes.
+/// The arrays can have arbitrary strides, given as the distance in elements between consecutive values along each axis.
+pub struct RealToComplexNd<T> {
+    shape: Vec<usize>,
+    r2c: Arc<dyn RealToComplex<T>>,
+    ffts: Vec<Arc<dyn rustfft::Fft<T>>>,
+    line_len: usize,
+    scratch_len: usize,
+}
+
+/// An N-dimensional iFFT that takes a complex half-spectrum, and transforms it to a real-valued array.
+///
+/// Complex iFFTs are first performed along all axes except the last one.
+/// This is followed by a complex-to-real iFFT along the last axis, which turns `n/2+1` complex values into `n` real values.
+/// The arrays can have arbitrary strides, given as the distance in elements between consecutive values along each axis.
+pub struct ComplexToRealNd<T> {
+    shape: Vec<usize>,
+    c2r: Arc<dyn ComplexToReal<T>>,
+    ffts: Vec<Arc<dyn rustfft::Fft<T>>>,
+    line_len: usize,
+    scratch_len: usize,
+}
+
+// Get the strides of a contiguous array stored in row-major order.
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for idx in (0..shape.len().saturating_sub(1)).rev() {
+        strides[idx] = strides[idx + 1] * shape[idx + 1];
+    }
+    strides
+}
+
+// Get the minimum length of a buffer holding an array of the given shape and strides.
+fn required_len(shape: &[usize], strides: &[usize]) -> usize {
+    if shape.contains(&0) {
+        return 0;
+    }
+    1 + shape
+        .iter()
+        .zip(strides.iter())
+        .map(|(dim, stride)| (dim - 1) * stride)
+        .sum::<usize>()
+}
+
+// Call `func` with the offsets of the first element of each line along `axis`, in two arrays of the same shape.
+fn for_each_line<F: FnMut(usize, usize) -> Res<()>>(
+    shape: &[usize],
+    axis: usize,
+    strides_a: &[usize],
+    strides_b: &[usize],
+    mut func: F,
+) -> Res<()> {
+    if shape.contains(&0) {
+        return Ok(());
+    }
+    let mut index = vec![0; shape.len()];
+    loop {
+        let offset_a = index.iter().zip(strides_a.iter()).map(|(i, s)| i * s).sum();
+        let offset_b = index.iter().zip(strides_b.iter()).map(|(i, s)| i * s).sum();
+        func(offset_a, offset_b)?;
+        // Step to the next line, skipping the axis of the lines
+        let mut dim = shape.len();
+        loop {
+            if dim == 0 {
+                return Ok(());
+            }
+            dim -= 1;
+            if dim == axis {
+                continue;
+            }
+            index[dim] += 1;
+            if index[dim] < shape[dim] {
+                break;
+            }
+            index[dim] = 0;
+        }
+    }
+}
+
+fn check_strides(shape: &[usize], strides: &[usize]) {
+    if strides.len() != shape.len() {
+        panic!(
+            "Expected strides for {} dimensions, got {}",
+            shape.len(),
+            strides.len()
+        );
+    }
+}
+
+impl<T: FftNum> RealToComplexNd<T> {
+    /// Create a new N-dimensional RealToComplex FFT for real input data of the given shape,
+    /// and uses the given RealFftPlanner to build the FFTs for each axis.
+    /// Panics if the shape is empty.
+    pub fn new(shape: &[usize], planner: &mut RealFftPlanner<T>) -> Self {
+        if shape.is_empty() {
+            panic!("Shape must have at least one dimension");
+        }
+        let last = shape[shape.len() - 1];
+        let r2c = planner.plan_fft_forward(last);
+        let ffts = shape[..shape.len() - 1]
+            .iter()
+            .map(|dim| planner.complex_planner().plan_fft_forward(*dim))
+            .collect::<Vec<_>>();
+        let inner_scratch_len = ffts
+            .iter()
+            .map(|fft| fft.get_inplace_scratch_len())
+            .fold(r2c.get_scratch_len(), usize::max);
+        let line_len = shape[..shape.len() - 1]
+            .iter()
+            .cloned()
+            .fold(last / 2 + 1, usize::max);
+        let scratch_len = (last + 1) / 2 + line_len + inner_scratch_len;
+        RealToComplexNd {
+            shape: shape.to_vec(),
+            r2c,
+            ffts,
+            line_len,
+            scratch_len,
+        }
+    }
+
+    /// Transform a contiguous real-valued array stored in row-major order, storing the result in the contiguous complex output.
+    /// The output has the same shape as the input, except for the last axis which has length `n/2+1`.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process(&self, input: &mut [T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a contiguous real-valued array stored in row-major order, storing the result in the contiguous complex output.
+    /// The output has the same shape as the input, except for the last axis which has length `n/2+1`.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let expected_input_buffer_size = self.shape.iter().product();
+        if input.len() != expected_input_buffer_size {
+            return Err(FftError::InputBuffer(
+                expected_input_buffer_size,
+                input.len(),
+            ));
+        }
+        let output_shape = self.output_shape();
+        let expected_output_buffer_size = output_shape.iter().product();
+        if output.len() != expected_output_buffer_size {
+            return Err(FftError::OutputBuffer(
+                expected_output_buffer_size,
+                output.len(),
+            ));
+        }
+        self.process_strided_with_scratch(
+            input,
+            &contiguous_strides(&self.shape),
+            output,
+            &contiguous_strides(&output_shape),
+            scratch,
+        )
+    }
+
+    /// Transform a real-valued array with the given strides, storing the result in the complex output with the given strides.
+    /// The output has the same shape as the input, except for the last axis which has length `n/2+1`.
+    /// The strides give the distance in elements between consecutive values along each axis.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices is too short for the shape and strides.
+    /// Panics if the number of strides does not match the number of dimensions.
+    pub fn process_strided_with_scratch(
+        &self,
+        input: &[T],
+        input_strides: &[usize],
+        output: &mut [Complex<T>],
+        output_strides: &[usize],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        check_strides(&self.shape, input_strides);
+        check_strides(&self.shape, output_strides);
+        let output_shape = self.output_shape();
+        let required_input_len = required_len(&self.shape, input_strides);
+        if input.len() < required_input_len {
+            return Err(FftError::InputBuffer(required_input_len, input.len()));
+        }
+        let required_output_len = required_len(&output_shape, output_strides);
+        if output.len() < required_output_len {
+            return Err(FftError::OutputBuffer(required_output_len, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let ndim = self.shape.len();
+        let last = self.shape[ndim - 1];
+        let (real_line, rest) = scratch.split_at_mut((last + 1) / 2);
+        let (line, fft_scratch) = rest.split_at_mut(self.line_len);
+        let real_line = &mut as_real(real_line)[..last];
+        let input_step = input_strides[ndim - 1];
+        let output_step = output_strides[ndim - 1];
+
+        // Real-to-complex FFTs along the last axis
+        for_each_line(
+            &self.shape,
+            ndim - 1,
+            input_strides,
+            output_strides,
+            |input_offset, output_offset| {
+                for (idx, val) in real_line.iter_mut().enumerate() {
+                    *val = input[input_offset + idx * input_step];
+                }
+                let spectrum = &mut line[..last / 2 + 1];
+                self.r2c
+                    .process_with_scratch(real_line, spectrum, fft_scratch)?;
+                for (idx, val) in spectrum.iter().enumerate() {
+                    output[output_offset + idx * output_step] = *val;
+                }
+                Ok(())
+            },
+        )?;
+
+        // Complex FFTs along the other axes
+        for (axis, fft) in self.ffts.iter().enumerate() {
+            let len = output_shape[axis];
+            let step = output_strides[axis];
+            let buffer = &mut line[..len];
+            for_each_line(
+                &output_shape,
+                axis,
+                output_strides,
+                output_strides,
+                |offset, _| {
+                    for (idx, val) in buffer.iter_mut().enumerate() {
+                        *val = output[offset + idx * step];
+                    }
+                    fft.process_with_scratch(buffer, fft_scratch);
+                    for (idx, val) in buffer.iter().enumerate() {
+                        output[offset + idx * step] = *val;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get the shape of the real-valued input.
+    pub fn input_shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Get the shape of the complex output, where the last axis has length `n/2+1`.
+    pub fn output_shape(&self) -> Vec<usize> {
+        let mut shape = self.shape.clone();
+        let last = shape.len() - 1;
+        shape[last] = shape[last] / 2 + 1;
+        shape
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Convenience method to make a contiguous input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.shape.iter().product()]
+    }
+
+    /// Convenience method to make a contiguous output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.output_shape().iter().product()]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> ComplexToRealNd<T> {
+    /// Create a new N-dimensional ComplexToReal iFFT for real output data of the given shape,
+    /// and uses the given RealFftPlanner to build the iFFTs for each axis.
+    /// Panics if the shape is empty.
+    pub fn new(shape: &[usize], planner: &mut RealFftPlanner<T>) -> Self {
+        if shape.is_empty() {
+            panic!("Shape must have at least one dimension");
+        }
+        let last = shape[shape.len() - 1];
+        let c2r = planner.plan_fft_inverse(last);
+        let ffts = shape[..shape.len() - 1]
+            .iter()
+            .map(|dim| planner.complex_planner().plan_fft_inverse(*dim))
+            .collect::<Vec<_>>();
+        let inner_scratch_len = ffts
+            .iter()
+            .map(|fft| fft.get_inplace_scratch_len())
+            .fold(c2r.get_scratch_len(), usize::max);
+        let line_len = shape[..shape.len() - 1]
+            .iter()
+            .cloned()
+            .fold(last / 2 + 1, usize::max);
+        let scratch_len = (last + 1) / 2 + line_len + inner_scratch_len;
+        ComplexToRealNd {
+            shape: shape.to_vec(),
+            c2r,
+            ffts,
+            line_len,
+            scratch_len,
+        }
+    }
+
+    /// Transform a contiguous complex half-spectrum stored in row-major order, storing the result in the contiguous real-valued output.
+    /// The input has the same shape as the output, except for the last axis which has length `n/2+1`.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// The input is assumed to be the spectrum of real-valued data. The imaginary parts that should be zero after
+    /// the transforms along the other axes are ignored, and no error is returned for them.
+    pub fn process(&self, input: &mut [Complex<T>], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a contiguous complex half-spectrum stored in row-major order, storing the result in the contiguous real-valued output.
+    /// The input has the same shape as the output, except for the last axis which has length `n/2+1`.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// The input is assumed to be the spectrum of real-valued data. The imaginary parts that should be zero after
+    /// the transforms along the other axes are ignored, and no error is returned for them.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [Complex<T>],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let input_shape = self.input_shape();
+        let expected_input_buffer_size = input_shape.iter().product();
+        if input.len() != expected_input_buffer_size {
+            return Err(FftError::InputBuffer(
+                expected_input_buffer_size,
+                input.len(),
+            ));
+        }
+        let expected_output_buffer_size = self.shape.iter().product();
+        if output.len() != expected_output_buffer_size {
+            return Err(FftError::OutputBuffer(
+                expected_output_buffer_size,
+                output.len(),
+            ));
+        }
+        self.process_strided_with_scratch(
+            input,
+            &contiguous_strides(&input_shape),
+            output,
+            &contiguous_strides(&self.shape),
+            scratch,
+        )
+    }
+
+    /// Transform a complex half-spectrum with the given strides, storing the result in the real-valued output with the given strides.
+    /// The input has the same shape as the output, except for the last axis which has length `n/2+1`.
+    /// The strides give the distance in elements between consecutive values along each axis.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices is too short for the shape and strides.
+    /// Panics if the number of strides does not match the number of dimensions.
+    /// The input is assumed to be the spectrum of real-valued data. The imaginary parts that should be zero after
+    /// the transforms along the other axes are ignored, and no error is returned for them.
+    pub fn process_strided_with_scratch(
+        &self,
+        input: &mut [Complex<T>],
+        input_strides: &[usize],
+        output: &mut [T],
+        output_strides: &[usize],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        check_strides(&self.shape, input_strides);
+        check_strides(&self.shape, output_strides);
+        let input_shape = self.input_shape();
+        let required_input_len = required_len(&input_shape, input_strides);
+        if input.len() < required_input_len {
+            return Err(FftError::InputBuffer(required_input_len, input.len()));
+        }
+        let required_output_len = required_len(&self.shape, output_strides);
+        if output.len() < required_output_len {
+            return Err(FftError::OutputBuffer(required_output_len, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let ndim = self.shape.len();
+        let last = self.shape[ndim - 1];
+        let (real_line, rest) = scratch.split_at_mut((last + 1) / 2);
+        let (line, fft_scratch) = rest.split_at_mut(self.line_len);
+        let real_line = &mut as_real(real_line)[..last];
+
+        // Complex iFFTs along all axes but the last
+        for (axis, fft) in self.ffts.iter().enumerate() {
+            let len = input_shape[axis];
+            let step = input_strides[axis];
+            let buffer = &mut line[..len];
+            for_each_line(
+                &input_shape,
+                axis,
+                input_strides,
+                input_strides,
+                |offset, _| {
+                    for (idx, val) in buffer.iter_mut().enumerate() {
+                        *val = input[offset + idx * step];
+                    }
+                    fft.process_with_scratch(buffer, fft_scratch);
+                    for (idx, val) in buffer.iter().enumerate() {
+                        input[offset + idx * step] = *val;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        // Complex-to-real iFFTs along the last axis
+        let input_step = input_strides[ndim - 1];
+        let output_step = output_strides[ndim - 1];
+        for_each_line(
+            &self.shape,
+            ndim - 1,
+            input_strides,
+            output_strides,
+            |input_offset, output_offset| {
+                let spectrum = &mut line[..last / 2 + 1];
+                for (idx, val) in spectrum.iter_mut().enumerate() {
+                    *val = input[input_offset + idx * input_step];
+                }
+                // Drop the imaginary parts that are only rounding errors from the other axes
+                spectrum[0].im = T::zero();
+                if last % 2 == 0 {
+                    spectrum[last / 2].im = T::zero();
+                }
+                self.c2r
+                    .process_with_scratch(spectrum, real_line, fft_scratch)?;
+                for (idx, val) in real_line.iter().enumerate() {
+                    output[output_offset + idx * output_step] = *val;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Get the shape of the complex input, where the last axis has length `n/2+1`.
+    pub fn input_shape(&self) -> Vec<usize> {
+        let mut shape = self.shape.clone();
+        let last = shape.len() - 1;
+        shape[last] = shape[last] / 2 + 1;
+        shape
+    }
+
+    /// Get the shape of the real-valued output.
+    pub fn output_shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Convenience method to make a contiguous input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.input_shape().iter().product()]
+    }
+
+    /// Convenience method to make a contiguous output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.shape.iter().product()]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComplexToRealNd, RealFftPlanner, RealToComplexNd};
+    use rand::Rng;
+    use rustfft::num_complex::Complex;
+    use rustfft::FftPlanner;
+
+    // Compare with a full N-dimensional complex FFT
+    #[test]
+    fn real_to_complex_nd() {
+        for shape in [vec![6], vec![3, 4, 5], vec![2, 5, 8], vec![4, 1, 3, 6]].iter() {
+            let mut planner = RealFftPlanner::<f64>::new();
+            let r2c = RealToComplexNd::new(shape, &mut planner);
+            let mut rng = rand::thread_rng();
+            let mut indata = r2c.make_input_vec();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let mut full = indata
+                .iter()
+                .map(Complex::from)
+                .collect::<Vec<Complex<f64>>>();
+            let mut output = r2c.make_output_vec();
+            r2c.process(&mut indata, &mut output).unwrap();
+
+            let mut fft_planner = FftPlanner::<f64>::new();
+            let strides = super::contiguous_strides(shape);
+            for axis in 0..shape.len() {
+                let fft = fft_planner.plan_fft_forward(shape[axis]);
+                super::for_each_line(shape, axis, &strides, &strides, |offset, _| {
+                    let mut line = (0..shape[axis])
+                        .map(|idx| full[offset + idx * strides[axis]])
+                        .collect::<Vec<_>>();
+                    fft.process(&mut line);
+                    for (idx, val) in line.iter().enumerate() {
+                        full[offset + idx * strides[axis]] = *val;
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
+            let output_shape = r2c.output_shape();
+            let output_strides = super::contiguous_strides(&output_shape);
+            super::for_each_line(
+                &output_shape,
+                shape.len() - 1,
+                &strides,
+                &output_strides,
+                |full_offset, output_offset| {
+                    for idx in 0..output_shape[shape.len() - 1] {
+                        let diff = (full[full_offset + idx] - output[output_offset + idx]).norm();
+                        assert!(
+                            diff < 1.0e-9,
+                            "Shape: {:?}, too large error: {}",
+                            shape,
+                            diff
+                        );
+                    }
+                    Ok(())
+                },
+            )
+            .unwrap();
+        }
+    }
+
+    // Transform column-major data with padding, and check that it matches the contiguous transform
+    // and that the inverse gives back the original data, scaled by the number of values
+    #[test]
+    fn strided_nd() {
+        for shape in [vec![3, 4, 5], vec![2, 5, 8]].iter() {
+            let mut planner = RealFftPlanner::<f64>::new();
+            let r2c = RealToComplexNd::new(shape, &mut planner);
+            let c2r = ComplexToRealNd::new(shape, &mut planner);
+            let mut rng = rand::thread_rng();
+            let mut indata = r2c.make_input_vec();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let original = indata.clone();
+            let mut expected = r2c.make_output_vec();
+            r2c.process(&mut indata, &mut expected).unwrap();
+
+            // Column-major with one padding value after each line along the first axis
+            let strides = [1, shape[0] + 1, (shape[0] + 1) * shape[1]];
+            let mut strided = vec![0.0; strides[2] * shape[2]];
+            let output_shape = r2c.output_shape();
+            let output_strides = [1, shape[0], shape[0] * output_shape[1]];
+            let contiguous_strides = super::contiguous_strides(shape);
+            super::for_each_line(shape, 0, &contiguous_strides, &strides, |from, to| {
+                for idx in 0..shape[0] {
+                    strided[to + idx] = original[from + idx * contiguous_strides[0]];
+                }
+                Ok(())
+            })
+            .unwrap();
+
+            let mut spectrum = vec![Complex::from(0.0); output_shape.iter().product()];
+            let mut scratch = r2c.make_scratch_vec();
+            r2c.process_strided_with_scratch(
+                &strided,
+                &strides,
+                &mut spectrum,
+                &output_strides,
+                &mut scratch,
+            )
+            .unwrap();
+            let expected_strides = super::contiguous_strides(&output_shape);
+            super::for_each_line(
+                &output_shape,
+                0,
+                &expected_strides,
+                &output_strides,
+                |from, to| {
+                    for idx in 0..shape[0] {
+                        let diff = (expected[from + idx * expected_strides[0]]
+                            - spectrum[to + idx])
+                            .norm();
+                        assert!(
+                            diff < 1.0e-9,
+                            "Shape: {:?}, too large error: {}",
+                            shape,
+                            diff
+                        );
+                    }
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+            let mut output = vec![0.0; strided.len()];
+            let mut scratch = c2r.make_scratch_vec();
+            c2r.process_strided_with_scratch(
+                &mut spectrum,
+                &output_strides,
+                &mut output,
+                &strides,
+                &mut scratch,
+            )
+            .unwrap();
+            let scale = shape.iter().product::<usize>() as f64;
+            super::for_each_line(shape, 0, &strides, &strides, |offset, _| {
+                for idx in 0..shape[0] {
+                    let diff = (strided[offset + idx] - output[offset + idx] / scale).abs();
+                    assert!(
+                        diff < 1.0e-9,
+                        "Shape: {:?}, too large error: {}",
+                        shape,
+                        diff
+                    );
+                }
+                Ok(())
+            })
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn strided_nd_errors() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let r2c = RealToComplexNd::new(&[4, 6], &mut planner);
+        let indata = vec![0.0; 24];
+        let mut spectrum = vec![Complex::from(0.0); 16];
+        let mut scratch = r2c.make_scratch_vec();
+        let res = r2c.process_strided_with_scratch(
+            &indata,
+            &[8, 1],
+            &mut spectrum,
+            &[4, 1],
+            &mut scratch,
+        );
+        assert!(res.is_err());
+        let res = r2c.process_strided_with_scratch(
+            &indata,
+            &[6, 1],
+            &mut spectrum,
+            &[5, 1],
+            &mut scratch,
+        );
+        assert!(res.is_err());
+        let res = r2c.process_strided_with_scratch(
+            &indata,
+            &[6, 1],
+            &mut spectrum,
+            &[4, 1],
+            &mut scratch,
+        );
+        assert!(res.is_ok());
+    }
+}
@@ -0,0 +1,291 @@
+use crate::next_fast_len;
+use crate::{ComplexToReal, FftError, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::sync::Arc;
+
+/// Selects how a `FirFilter` combines the results of consecutive blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirMethod {
+    /// Each block is zero-padded and filtered separately, and the tails of the results are added to the following blocks.
+    OverlapAdd,
+    /// Each block is filtered together with the preceding input, and the part of the result affected by circular wrap-around is discarded.
+    OverlapSave,
+}
+
+// The input and output blocks of a streaming processor, and the position in the current blocks.
+// The output block holds the result of the previous input block, so the output is delayed by one block.
+pub(crate) struct BlockBuffer<T> {
+    pub(crate) input: Vec<T>,
+    pub(crate) output: Vec<T>,
+    position: usize,
+}
+
+impl<T: FftNum> BlockBuffer<T> {
+    pub(crate) fn new(block_len: usize) -> Self {
+        BlockBuffer {
+            input: vec![T::zero(); block_len],
+            output: vec![T::zero(); block_len],
+            position: 0,
+        }
+    }
+
+    // Clear both blocks, and start a new block.
+    pub(crate) fn reset(&mut self) {
+        for val in self.input.iter_mut().chain(self.output.iter_mut()) {
+            *val = T::zero();
+        }
+        self.position = 0;
+    }
+}
+
+// A streaming processor that collects the input into blocks, and processes each block when it is full.
+pub(crate) trait BlockProcessor<T: FftNum> {
+    fn blocks(&mut self) -> &mut BlockBuffer<T>;
+
+    // Process the collected input block, and store the result in the output block.
+    fn process_block(&mut self) -> Res<()>;
+
+    // Exchange a chunk of input samples for the same number of output samples from the output blocks.
+    // An error is returned if the output does not have the same length as the input.
+    fn process_chunk(&mut self, input: &[T], output: &mut [T]) -> Res<()> {
+        if output.len() != input.len() {
+            return Err(FftError::OutputBuffer(input.len(), output.len()));
+        }
+        let mut done = 0;
+        while done < input.len() {
+            let blocks = self.blocks();
+            let block_len = blocks.input.len();
+            let count = (block_len - blocks.position).min(input.len() - done);
+            let range = blocks.position..blocks.position + count;
+            output[done..done + count].copy_from_slice(&blocks.output[range.clone()]);
+            blocks.input[range].copy_from_slice(&input[done..done + count]);
+            blocks.position += count;
+            done += count;
+            if blocks.position == block_len {
+                self.process_block()?;
+                self.blocks().position = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A streaming FIR filter, that filters a signal using FFT-based convolution.
+///
+/// The spectrum of the filter kernel is calculated once, when the filter is created.
+/// The input is collected into blocks of `block_len` samples, which are filtered using a real FFT
+/// of a length that fits a block plus the kernel. This means that the output is delayed by `block_len` samples.
+/// The input may be given in chunks of any size, and the same number of output samples is returned for each chunk.
+///
+/// All buffers are allocated when the filter is created, so processing does not allocate.
+pub struct FirFilter<T: FftNum> {
+    block_len: usize,
+    method: FirMethod,
+    r2c: Arc<dyn RealToComplex<T>>,
+    c2r: Arc<dyn ComplexToReal<T>>,
+    kernel_spectrum: Vec<Complex<T>>,
+    blocks: BlockBuffer<T>,
+    // The tail of the previous results for overlap-add, and the preceding input for overlap-save
+    history: Vec<T>,
+    buffer: Vec<T>,
+    spectrum: Vec<Complex<T>>,
+    scratch: Vec<Complex<T>>,
+}
+
+impl<T: FftNum> FirFilter<T> {
+    /// Create a new FIR filter for the given kernel, that processes the input in blocks of `block_len` samples.
+    /// Uses the given RealFftPlanner to build the FFTs.
+    /// Panics if the kernel is empty or if `block_len` is zero.
+    pub fn new(
+        kernel: &[T],
+        block_len: usize,
+        method: FirMethod,
+        planner: &mut RealFftPlanner<T>,
+    ) -> Self {
+        if kernel.is_empty() {
+            panic!("The filter kernel must not be empty");
+        }
+        if block_len == 0 {
+            panic!("The block length must be at least 1");
+        }
+        let fft_len = next_fast_len(block_len + kernel.len() - 1);
+        let r2c = planner.plan_fft_forward(fft_len);
+        let c2r = planner.plan_fft_inverse(fft_len);
+        let mut scratch = vec![Complex::zero(); r2c.get_scratch_len().max(c2r.get_scratch_len())];
+
+        // Include the scaling of the iFFT in the kernel spectrum
+        let mut buffer = r2c.make_input_vec();
+        let scale = T::one() / T::from_usize(fft_len).unwrap();
+        for (val, coeff) in buffer.iter_mut().zip(kernel.iter()) {
+            *val = *coeff * scale;
+        }
+        let mut kernel_spectrum = r2c.make_output_vec();
+        r2c.process_with_scratch(&mut buffer, &mut kernel_spectrum, &mut scratch)
+            .unwrap();
+
+        FirFilter {
+            block_len,
+            method,
+            kernel_spectrum,
+            blocks: BlockBuffer::new(block_len),
+            history: vec![T::zero(); fft_len - block_len],
+            buffer,
+            spectrum: r2c.make_output_vec(),
+            scratch,
+            r2c,
+            c2r,
+        }
+    }
+
+    /// Filter a chunk of input samples, storing the same number of filtered samples in the output.
+    /// The output is delayed by `block_len` samples.
+    /// An error is returned if the output does not have the same length as the input.
+    pub fn process(&mut self, input: &[T], output: &mut [T]) -> Res<()> {
+        self.process_chunk(input, output)
+    }
+
+    /// Clear the internal state, as if no input had been processed.
+    pub fn reset(&mut self) {
+        self.blocks.reset();
+        for val in self.history.iter_mut() {
+            *val = T::zero();
+        }
+    }
+
+    /// Get the number of samples that the output is delayed, which equals the block length.
+    pub fn latency(&self) -> usize {
+        self.block_len
+    }
+
+    /// Get the length of the blocks that the input is processed in.
+    pub fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// Get the length of the FFTs used for filtering the blocks.
+    pub fn fft_len(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+impl<T: FftNum> BlockProcessor<T> for FirFilter<T> {
+    fn blocks(&mut self) -> &mut BlockBuffer<T> {
+        &mut self.blocks
+    }
+
+    fn process_block(&mut self) -> Res<()> {
+        let block_len = self.block_len;
+        let overlap_len = self.history.len();
+        match self.method {
+            FirMethod::OverlapAdd => {
+                self.buffer[..block_len].copy_from_slice(&self.blocks.input);
+                for val in self.buffer[block_len..].iter_mut() {
+                    *val = T::zero();
+                }
+            }
+            FirMethod::OverlapSave => {
+                self.buffer[..overlap_len].copy_from_slice(&self.history);
+                self.buffer[overlap_len..].copy_from_slice(&self.blocks.input);
+                // Keep the last samples for the next block
+                let fft_len = self.buffer.len();
+                self.history
+                    .copy_from_slice(&self.buffer[fft_len - overlap_len..]);
+            }
+        }
+        self.r2c
+            .process_with_scratch(&mut self.buffer, &mut self.spectrum, &mut self.scratch)?;
+        for (val, coeff) in self.spectrum.iter_mut().zip(self.kernel_spectrum.iter()) {
+            *val = *val * coeff;
+        }
+        self.c2r
+            .process_with_scratch(&mut self.spectrum, &mut self.buffer, &mut self.scratch)?;
+        match self.method {
+            FirMethod::OverlapAdd => {
+                for (out, (val, tail)) in self
+                    .blocks
+                    .output
+                    .iter_mut()
+                    .zip(self.buffer.iter().zip(self.history.iter()))
+                {
+                    *out = *val + *tail;
+                }
+                if overlap_len < block_len {
+                    self.blocks.output[overlap_len..]
+                        .copy_from_slice(&self.buffer[overlap_len..block_len]);
+                }
+                // Keep what remains of the old tail, and add the new one
+                if overlap_len > block_len {
+                    self.history.copy_within(block_len.., 0);
+                }
+                let kept = overlap_len.saturating_sub(block_len);
+                for val in self.history[kept..].iter_mut() {
+                    *val = T::zero();
+                }
+                for (tail, val) in self.history.iter_mut().zip(self.buffer[block_len..].iter()) {
+                    *tail = *tail + *val;
+                }
+            }
+            FirMethod::OverlapSave => {
+                self.blocks
+                    .output
+                    .copy_from_slice(&self.buffer[overlap_len..]);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FirFilter, FirMethod, RealFftPlanner};
+    use rand::Rng;
+
+    // Filter a signal in chunks of random size, and compare with a direct convolution
+    #[test]
+    fn fir_filter() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut rng = rand::thread_rng();
+        for &method in [FirMethod::OverlapAdd, FirMethod::OverlapSave].iter() {
+            for &(kernel_len, block_len) in [(1, 1), (5, 16), (16, 5), (33, 32), (100, 7)].iter() {
+                let kernel = (0..kernel_len)
+                    .map(|_| rng.gen::<f64>())
+                    .collect::<Vec<f64>>();
+                let signal = (0..500).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+                let mut filter = FirFilter::new(&kernel, block_len, method, &mut planner);
+                let mut output = vec![0.0; signal.len()];
+                let mut done = 0;
+                while done < signal.len() {
+                    let count = rng.gen_range(0..20).min(signal.len() - done);
+                    filter
+                        .process(&signal[done..done + count], &mut output[done..done + count])
+                        .unwrap();
+                    done += count;
+                }
+                for (n, val) in output.iter().enumerate() {
+                    let expected = if n < block_len {
+                        0.0
+                    } else {
+                        let time = n - block_len;
+                        kernel
+                            .iter()
+                            .enumerate()
+                            .filter(|(k, _)| *k <= time)
+                            .map(|(k, coeff)| coeff * signal[time - k])
+                            .sum::<f64>()
+                    };
+                    assert!(
+                        (expected - val).abs() < 1.0e-9,
+                        "Method: {:?}, kernel: {}, block: {}, sample: {}, too large error: {}",
+                        method,
+                        kernel_len,
+                        block_len,
+                        n,
+                        (expected - val).abs()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{FftError, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::{Fft, FftNum};
+use std::sync::Arc;
+
+/// A Hilbert transform, that calculates the analytic signal of a real-valued signal.
+///
+/// The real part of the result is the input signal, and the imaginary part is its Hilbert transform.
+/// The magnitude of the result gives the envelope, and the derivative of its phase gives the instantaneous frequency.
+///
+/// The signal is transformed with a real-to-complex FFT, and the spectrum of the analytic signal is formed
+/// by doubling the positive frequencies and setting the negative ones to zero.
+/// The first value, the DC component, is not doubled. For even lengths, the last value of the real-to-complex FFT
+/// holds the Nyquist frequency, which is both positive and negative, and is not doubled either.
+/// The spectrum is then transformed back using a complex iFFT, and the result is scaled by `1/N`.
+pub struct Hilbert<T> {
+    length: usize,
+    fft: Arc<dyn RealToComplex<T>>,
+    ifft: Arc<dyn Fft<T>>,
+    scratch_len: usize,
+}
+
+impl<T: FftNum> Hilbert<T> {
+    /// Create a new Hilbert transform for signals of the given length.
+    /// Uses the given RealFftPlanner to build the FFT and the complex iFFT.
+    pub fn new(length: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        let fft = planner.plan_fft_forward(length);
+        let ifft = planner.complex_planner().plan_fft_inverse(length);
+        let scratch_len = fft.get_scratch_len().max(ifft.get_inplace_scratch_len());
+        Hilbert {
+            length,
+            fft,
+            ifft,
+            scratch_len,
+        }
+    }
+
+    /// Calculate the analytic signal of the input, storing the result in the output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if the input or output has the wrong length.
+    pub fn process(&self, input: &mut [T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Calculate the analytic signal of the input, storing the result in the output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which makes this the faster of the two.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        if self.length == 0 {
+            return Ok(());
+        }
+        let bins = self.length / 2 + 1;
+        let (spectrum, negative) = output.split_at_mut(bins);
+        self.fft.process_with_scratch(
+            input,
+            spectrum,
+            &mut scratch[..self.fft.get_scratch_len()],
+        )?;
+
+        // Double the positive frequencies, and leave the DC and Nyquist bins as they are.
+        // The iFFT scaling is included in the same step.
+        let scale = T::one() / T::from_usize(self.length).unwrap();
+        let doubled_scale = scale + scale;
+        let last_doubled = if self.length % 2 == 0 { bins - 1 } else { bins };
+        for (k, val) in spectrum.iter_mut().enumerate() {
+            let factor = if k > 0 && k < last_doubled {
+                doubled_scale
+            } else {
+                scale
+            };
+            *val = *val * factor;
+        }
+        for val in negative.iter_mut() {
+            *val = Complex::zero();
+        }
+        self.ifft
+            .process_with_scratch(output, &mut scratch[..self.ifft.get_inplace_scratch_len()]);
+        Ok(())
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// The length of the signals.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.length]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.length]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FftError, Hilbert, RealFftPlanner};
+    use rand::Rng;
+    use rustfft::num_complex::Complex;
+    use std::f64::consts::PI;
+
+    // Check the analytic signals of sinusoids and random signals, for even and odd lengths
+    #[test]
+    fn hilbert() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut rng = rand::thread_rng();
+        for &len in [1, 2, 15, 16, 30, 35].iter() {
+            let hilbert = Hilbert::new(len, &mut planner);
+            let mut output = hilbert.make_output_vec();
+
+            // The analytic signal of a cosine is a complex exponential
+            if len > 2 {
+                let phase = |n: usize| 2.0 * PI * (2 * n) as f64 / len as f64 + 0.3;
+                let mut input: Vec<f64> = (0..len).map(|n| 1.5 * phase(n).cos()).collect();
+                hilbert.process(&mut input, &mut output).unwrap();
+                for (n, val) in output.iter().enumerate() {
+                    let expected = Complex::from_polar(1.5, phase(n));
+                    assert!((val - expected).norm() < 1.0e-9, "{} {}", len, n);
+                }
+            }
+
+            // The real part is the input, including the DC and Nyquist components
+            let signal: Vec<f64> = (0..len).map(|_| rng.gen::<f64>()).collect();
+            let mut input = signal.clone();
+            hilbert.process(&mut input, &mut output).unwrap();
+            for (val, x) in output.iter().zip(signal.iter()) {
+                assert!((val.re - x).abs() < 1.0e-9, "{}", len);
+            }
+        }
+
+        // The Hilbert transform of an even length Nyquist component is zero
+        let hilbert = Hilbert::new(8, &mut planner);
+        let mut input: Vec<f64> = (0..8)
+            .map(|n| if n % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        let mut output = hilbert.make_output_vec();
+        hilbert.process(&mut input, &mut output).unwrap();
+        assert!(output.iter().all(|val| val.im.abs() < 1.0e-12));
+
+        let res = hilbert.process(&mut input, &mut output[..7]);
+        assert!(matches!(res, Err(FftError::OutputBuffer(8, 7))));
+    }
+}
@@ -10,6 +10,15 @@
 //!
 //! The iFFT goes through the same steps backwards, to transform an N+1 long complex spectrum to a 2N long real result.
 //!
+//! Odd lengths can't be packed like this. Instead, an odd length N that is divisible by p = 3, 5, 7, 11 or 13
+//! is split into p interleaved subsequences, that are transformed pairwise by complex FFTs of length N/p.
+//! By default this is done when N is at least 150 and N/p has a prime factor larger than 7,
+//! or when N is at least 500 for p = 3, 2000 for p = 5 or 13, and 5000 for p = 7 or 11.
+//! Odd prime lengths from 37 and up use Rader's algorithm, which calculates the FFT as a convolution using real FFTs.
+//! All other odd lengths, for example 441 = 3²·7², are transformed by a complex FFT of the full length,
+//! since RustFFT is faster than the alternatives for these.
+//! The other algorithms can still be chosen using `PlanningMode::Measure` or by importing wisdom.
+//!
 //! The speed increase compared to just converting the input to a 2N long complex vector
 //! and using a 2N long FFT depends on the length of the input data.
 //! The largest improvements are for long FFTs and for lengths over around 1000 elements there is an improvement of about a factor 2.
@@ -70,6 +79,8 @@
 //! ## Scaling
 //! RealFFT matches the behaviour of RustFFT and does not normalize the output of either FFT of iFFT. To get normalized results, each element must be scaled by `1/sqrt(length)`. If the processing involves both an FFT and an iFFT step, it is advisable to merge the two normalization steps to a single, by scaling by `1/length`.
 //!
+//! Alternatively, the planner can include the scaling in the transforms, using `plan_fft_forward_normalized` and `plan_fft_inverse_normalized`. These take a `Normalization` that works like the `norm` argument of `numpy.fft`: `Backward` scales the iFFT by `1/length`, `Forward` scales the FFT by `1/length`, and `Ortho` scales both by `1/sqrt(length)`. The scaling is merged into the pre- and post-processing steps, so it does not require an extra pass over the data.
+//!
 //! ## Documentation
 //!
 //! The full documentation can be generated by rustdoc. To generate and view it run:
@@ -139,10 +150,52 @@ pub use rustfft::num_complex;
 pub use rustfft::num_traits;
 pub use rustfft::FftNum;
 
+mod cache;
+mod convolve;
+mod czt;
+mod dct;
+mod dht;
+mod dst;
+mod fft2d;
+mod fftnd;
+mod fir;
+mod hilbert;
+mod mdct;
+mod measure;
+mod partitioned;
+mod psd;
+mod rader;
+mod resample;
+mod shared;
+mod stft;
+mod window;
+mod wisdom;
+
+pub use crate::convolve::{ConvolutionMode, Convolver};
+pub use crate::czt::ZoomFft;
+pub use crate::dct::{Dct2, Dct3, Dct4, DctPlanner};
+pub use crate::dht::Dht;
+pub use crate::dst::{Dst1, Dst2, Dst3, Dst4, DstPlanner};
+pub use crate::fft2d::{ComplexToReal2d, RealFftPlanner2d, RealToComplex2d};
+pub use crate::fftnd::{ComplexToRealNd, RealToComplexNd};
+pub use crate::fir::{FirFilter, FirMethod};
+pub use crate::hilbert::Hilbert;
+pub use crate::mdct::{sine_window, vorbis_window, Imdct, Mdct};
+pub use crate::partitioned::PartitionedConvolver;
+pub use crate::psd::{Periodogram, PsdScaling, Welch};
+pub use crate::resample::FftResampler;
+pub use crate::shared::SharedRealFftPlanner;
+pub use crate::stft::{Istft, PaddingMode, Stft};
+pub use crate::window::{Window, WindowedRealToComplex};
+pub use crate::wisdom::{Algorithm, WisdomError};
+
+use crate::cache::LruCache;
+use crate::measure::{fastest_forward, fastest_inverse};
+use crate::rader::{is_prime, ComplexToRealRader, RealToComplexRader};
+use crate::wisdom::{Choice, Wisdom};
 use rustfft::num_complex::Complex;
 use rustfft::num_traits::Zero;
-use rustfft::FftPlanner;
-use std::collections::HashMap;
+use rustfft::{Fft, FftPlanner, FftPlannerScalar};
 use std::error;
 use std::fmt;
 use std::sync::Arc;
@@ -217,6 +270,42 @@ impl fmt::Display for FftError {
 
 impl error::Error for FftError {}
 
+/// Selects how the results of the transforms are scaled, with the same meaning as the `norm` argument of `numpy.fft`.
+/// The scaling is included in the pre- or post-processing of the transforms, and does not need an extra pass over the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Normalization {
+    /// Neither the forward nor the inverse transform is scaled. This matches RustFFT.
+    None,
+    /// The forward transform is scaled by `1/N`, and the inverse is not scaled.
+    Forward,
+    /// The forward transform is not scaled, and the inverse is scaled by `1/N`.
+    Backward,
+    /// Both the forward and the inverse transforms are scaled by `1/sqrt(N)`.
+    Ortho,
+}
+
+impl Normalization {
+    // Get the factor the forward transform of length `len` is scaled by.
+    fn forward_scale<T: FftNum>(self, len: usize) -> T {
+        let scale = match self {
+            Normalization::None | Normalization::Backward => 1.0,
+            Normalization::Forward => 1.0 / len as f64,
+            Normalization::Ortho => 1.0 / (len as f64).sqrt(),
+        };
+        T::from_f64(scale).unwrap()
+    }
+
+    // Get the factor the inverse transform of length `len` is scaled by.
+    fn inverse_scale<T: FftNum>(self, len: usize) -> T {
+        let scale = match self {
+            Normalization::None | Normalization::Forward => 1.0,
+            Normalization::Backward => 1.0 / len as f64,
+            Normalization::Ortho => 1.0 / (len as f64).sqrt(),
+        };
+        T::from_f64(scale).unwrap()
+    }
+}
+
 fn compute_twiddle<T: FftNum>(index: usize, fft_len: usize) -> Complex<T> {
     let constant = -2f64 * std::f64::consts::PI / fft_len as f64;
     let angle = constant * index as f64;
@@ -226,9 +315,146 @@ fn compute_twiddle<T: FftNum>(index: usize, fft_len: usize) -> Complex<T> {
     }
 }
 
+// Use part of a complex scratch buffer as a real-valued buffer of twice the length.
+fn as_real<T>(buffer: &mut [Complex<T>]) -> &mut [T] {
+    unsafe {
+        let ptr = buffer.as_mut_ptr() as *mut T;
+        let len = buffer.len();
+        std::slice::from_raw_parts_mut(ptr, 2 * len)
+    }
+}
+
+// The radices that odd lengths can be split with, in the order they are tried.
+// Radix 3 and 5 have their own DFTs, the others use `dft_odd`.
+pub(crate) const SPLIT_RADICES: [usize; 5] = [13, 3, 5, 11, 7];
+const MAX_SPLIT_RADIX: usize = 13;
+
+// The shortest odd lengths that are split with each of the radices in `SPLIT_RADICES`,
+// chosen from the "Odd split" benches. Below these, the recombination step costs more than what is saved on the inner FFTs.
+const MIN_SPLIT_LENS: [usize; 5] = [2000, 500, 2000, 5000, 5000];
+
+// The shortest odd length that is split when the inner length has a prime factor larger than 7.
+// RustFFT is slower for such lengths, so splitting pays off also for shorter lengths.
+const MIN_SPLIT_LEN_SLOW: usize = 150;
+
+// The shortest prime length that uses Rader's algorithm, chosen from the "Odd prime" benches.
+const MIN_RADER_LEN: usize = 37;
+
+// Find the radix to use for an odd length.
+// Only lengths divisible by one of the split radices are split into subsequences,
+// for other lengths this returns 1 to tell that the length is transformed without splitting.
+fn odd_radix(length: usize) -> usize {
+    SPLIT_RADICES
+        .iter()
+        .zip(MIN_SPLIT_LENS.iter())
+        .find(|(radix, min_len)| {
+            length % **radix == 0
+                && (length >= **min_len
+                    || length >= MIN_SPLIT_LEN_SLOW
+                        && !has_only_factors(length / **radix, &[3, 5, 7]))
+        })
+        .map_or(1, |(radix, _)| *radix)
+}
+
+// Separate the spectra of two real-valued sequences, that were transformed together by packing them
+// in the real and imaginary parts of a complex sequence.
+// Takes the values at index k and N-k of the complex spectrum, and returns the values at index k of the two real spectra.
+fn split_pair<T: FftNum>(value: Complex<T>, value_rev: Complex<T>) -> (Complex<T>, Complex<T>) {
+    let half = T::from_f64(0.5).unwrap();
+    let value_rev = value_rev.conj();
+    let sum = value + value_rev;
+    let diff = value - value_rev;
+    (sum * half, Complex::new(diff.im * half, -diff.re * half))
+}
+
+// The reverse of `split_pair`. Takes the values at index k of the spectra of two real-valued sequences,
+// and returns the values at index k and N-k of the spectrum of the complex sequence that contains them
+// in its real and imaginary parts.
+fn join_pair<T: FftNum>(first: Complex<T>, second: Complex<T>) -> (Complex<T>, Complex<T>) {
+    (
+        Complex::new(first.re - second.im, first.im + second.re),
+        Complex::new(first.re + second.im, second.re - first.im),
+    )
+}
+
+// DFT of length 3. The sign of `sin` determines the direction, positive for forward and negative for inverse.
+fn dft3<T: FftNum>(x0: Complex<T>, x1: Complex<T>, x2: Complex<T>, sin: T) -> [Complex<T>; 3] {
+    let half = T::from_f64(0.5).unwrap();
+    let sum = x1 + x2;
+    let diff = (x1 - x2) * sin;
+    let common = x0 - sum * half;
+    let rotated = Complex::new(-diff.im, diff.re);
+    [x0 + sum, common - rotated, common + rotated]
+}
+
+// DFT of length 5. The twiddles are the cosine and sine of 2*pi/5 and 4*pi/5,
+// with the sign of the sines determining the direction like for `dft3`.
+fn dft5<T: FftNum>(x: [Complex<T>; 5], twiddles: &[Complex<T>]) -> [Complex<T>; 5] {
+    let (tw1, tw2) = (twiddles[0], twiddles[1]);
+    let sum1 = x[1] + x[4];
+    let diff1 = x[1] - x[4];
+    let sum2 = x[2] + x[3];
+    let diff2 = x[2] - x[3];
+    let common1 = x[0] + sum1 * tw1.re + sum2 * tw2.re;
+    let common2 = x[0] + sum1 * tw2.re + sum2 * tw1.re;
+    let diff_sin1 = diff1 * tw1.im + diff2 * tw2.im;
+    let diff_sin2 = diff1 * tw2.im - diff2 * tw1.im;
+    let rotated1 = Complex::new(-diff_sin1.im, diff_sin1.re);
+    let rotated2 = Complex::new(-diff_sin2.im, diff_sin2.re);
+    [
+        x[0] + sum1 + sum2,
+        common1 - rotated1,
+        common2 - rotated2,
+        common2 + rotated2,
+        common1 + rotated1,
+    ]
+}
+
+// The twiddle factors `exp(2*pi*i*j*k/radix)` used by the DFTs of length `radix`, for `j` and `k` in `1..=radix/2`,
+// with the values for each `k` stored together. The first ones are the values for `dft3` and `dft5`.
+fn compute_radix_twiddles<T: FftNum>(radix: usize) -> Vec<Complex<T>> {
+    let half_radix = (radix - 1) / 2;
+    (1..=half_radix)
+        .flat_map(|k| {
+            (1..=half_radix).map(move |j| compute_twiddle::<T>(j * k % radix, radix).conj())
+        })
+        .collect()
+}
+
+// DFT of any odd length up to `MAX_SPLIT_RADIX`, using the same symmetry as `dft5`.
+// The twiddles are the ones from `compute_radix_twiddles`, conjugated for the inverse.
+fn dft_odd<T: FftNum>(x: &[Complex<T>], twiddles: &[Complex<T>], output: &mut [Complex<T>]) {
+    let radix = x.len();
+    let half_radix = (radix - 1) / 2;
+    let mut sums = [Complex::zero(); MAX_SPLIT_RADIX / 2];
+    let mut diffs = [Complex::zero(); MAX_SPLIT_RADIX / 2];
+    output[0] = x[0];
+    for j in 1..=half_radix {
+        sums[j - 1] = x[j] + x[radix - j];
+        diffs[j - 1] = x[j] - x[radix - j];
+        output[0] = output[0] + sums[j - 1];
+    }
+    for (k, twiddles) in (1..=half_radix).zip(twiddles.chunks_exact(half_radix)) {
+        let mut common = x[0];
+        let mut diff_sin = Complex::<T>::zero();
+        for (twiddle, (sum, diff)) in twiddles.iter().zip(sums.iter().zip(diffs.iter())) {
+            common = common + *sum * twiddle.re;
+            diff_sin = diff_sin + *diff * twiddle.im;
+        }
+        let rotated = Complex::new(-diff_sin.im, diff_sin.re);
+        output[k] = common - rotated;
+        output[radix - k] = common + rotated;
+    }
+}
+
 pub struct RealToComplexOdd<T> {
+    twiddles: Vec<Complex<T>>,
+    radix_twiddles: Vec<Complex<T>>,
     length: usize,
+    radix: usize,
     fft: std::sync::Arc<dyn rustfft::Fft<T>>,
+    inner_r2c: Option<Arc<dyn RealToComplex<T>>>,
+    scale: T,
     scratch_len: usize,
 }
 
@@ -236,17 +462,32 @@ pub struct RealToComplexEven<T> {
     twiddles: Vec<Complex<T>>,
     length: usize,
     fft: std::sync::Arc<dyn rustfft::Fft<T>>,
+    scale: T,
     scratch_len: usize,
 }
 
 pub struct ComplexToRealOdd<T> {
+    twiddles: Vec<Complex<T>>,
+    radix_twiddles: Vec<Complex<T>>,
     length: usize,
+    radix: usize,
     fft: std::sync::Arc<dyn rustfft::Fft<T>>,
+    inner_c2r: Option<Arc<dyn ComplexToReal<T>>>,
+    scale: T,
     scratch_len: usize,
 }
 
 pub struct ComplexToRealEven<T> {
     twiddles: Vec<Complex<T>>,
+    length: usize,
+    fft: std::sync::Arc<dyn rustfft::Fft<T>>,
+    scale: T,
+    scratch_len: usize,
+}
+
+/// An FFT that transforms two real-valued signals of the same length at once,
+/// by packing them into the real and imaginary parts of a single complex FFT.
+pub struct RealToComplexPair<T> {
     length: usize,
     fft: std::sync::Arc<dyn rustfft::Fft<T>>,
     scratch_len: usize,
@@ -287,6 +528,54 @@ pub trait RealToComplex<T>: Sync + Send {
 
     /// Convenience method to make a scratch vector of the right type and length.
     fn make_scratch_vec(&self) -> Vec<Complex<T>>;
+
+    /// Transform a batch of signals of N real-valued samples each, stored one after the other in the input.
+    /// The results are stored one after the other in the output, as N/2+1 (with N/2 rounded down) complex values per signal.
+    /// The number of signals is given by the length of the input, which must be a multiple of N.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed, once for the whole batch.
+    /// An error is returned if any of the given slices has the wrong length.
+    fn process_batch(&self, input: &mut [T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_batch_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a batch of signals of N real-valued samples each, stored one after the other in the input.
+    /// The results are stored one after the other in the output, as N/2+1 (with N/2 rounded down) complex values per signal.
+    /// The number of signals is given by the length of the input, which must be a multiple of N.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector for all signals, instead of allocating.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// The lengths are checked once for the whole batch, before any transform is performed.
+    fn process_batch_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let length = self.len();
+        let output_length = length / 2 + 1;
+        let count = input.len().checked_div(length).unwrap_or(0);
+        if input.len() != count * length {
+            return Err(FftError::InputBuffer(count * length, input.len()));
+        }
+        if output.len() != count * output_length {
+            return Err(FftError::OutputBuffer(count * output_length, output.len()));
+        }
+        if scratch.len() < self.get_scratch_len() {
+            return Err(FftError::ScratchBuffer(
+                self.get_scratch_len(),
+                scratch.len(),
+            ));
+        }
+        for (signal, spectrum) in input
+            .chunks_exact_mut(length.max(1))
+            .zip(output.chunks_exact_mut(output_length))
+        {
+            self.process_with_scratch(signal, spectrum, scratch)?;
+        }
+        Ok(())
+    }
 }
 
 /// An FFT that takes a complex-valued input vector of length N+1 and transforms it to a complex
@@ -330,6 +619,108 @@ pub trait ComplexToReal<T>: Sync + Send {
 
     /// Convenience method to make a scratch vector of the right type and length.
     fn make_scratch_vec(&self) -> Vec<Complex<T>>;
+
+    /// Transform a batch of complex spectra of N/2+1 (with N/2 rounded down) values each, stored one after the other in the input.
+    /// The real results are stored one after the other in the output, as N values per spectrum.
+    /// The number of spectra is given by the length of the input, which must be a multiple of N/2+1.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed, once for the whole batch.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// If the input data is invalid, meaning that one of the positions that should contain a zero holds a different value,
+    /// the transforms are still performed. The function then returns an `FftError::InputValues` error to tell that the
+    /// result may not be correct. The flags of the error are set if the corresponding value was invalid in any of the spectra.
+    fn process_batch(&self, input: &mut [Complex<T>], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_batch_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a batch of complex spectra of N/2+1 (with N/2 rounded down) values each, stored one after the other in the input.
+    /// The real results are stored one after the other in the output, as N values per spectrum.
+    /// The number of spectra is given by the length of the input, which must be a multiple of N/2+1.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector for all spectra, instead of allocating.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// The lengths are checked once for the whole batch, before any transform is performed.
+    /// If the input data is invalid, meaning that one of the positions that should contain a zero holds a different value,
+    /// the transforms are still performed. The function then returns an `FftError::InputValues` error to tell that the
+    /// result may not be correct. The flags of the error are set if the corresponding value was invalid in any of the spectra.
+    fn process_batch_with_scratch(
+        &self,
+        input: &mut [Complex<T>],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let length = self.len();
+        let input_length = length / 2 + 1;
+        let count = input.len() / input_length;
+        if input.len() != count * input_length {
+            return Err(FftError::InputBuffer(count * input_length, input.len()));
+        }
+        if output.len() != count * length {
+            return Err(FftError::OutputBuffer(count * length, output.len()));
+        }
+        if scratch.len() < self.get_scratch_len() {
+            return Err(FftError::ScratchBuffer(
+                self.get_scratch_len(),
+                scratch.len(),
+            ));
+        }
+        let mut first_invalid = false;
+        let mut last_invalid = false;
+        for (spectrum, signal) in input
+            .chunks_exact_mut(input_length)
+            .zip(output.chunks_exact_mut(length.max(1)))
+        {
+            match self.process_with_scratch(spectrum, signal, scratch) {
+                Err(FftError::InputValues(first, last)) => {
+                    first_invalid |= first;
+                    last_invalid |= last;
+                }
+                result => result?,
+            }
+        }
+        if first_invalid || last_invalid {
+            return Err(FftError::InputValues(first_invalid, last_invalid));
+        }
+        Ok(())
+    }
+}
+
+/// A transform that takes a real-valued input vector of length N and transforms it to a real-valued
+/// output vector of the same length.
+#[allow(clippy::len_without_is_empty)]
+pub trait RealToReal<T>: Sync + Send {
+    /// Transform a vector of N real-valued samples, storing the result in the N element long real output vector.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()>;
+
+    /// Transform a vector of N real-valued samples, storing the result in the N element long real output vector.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()>;
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    fn get_scratch_len(&self) -> usize;
+
+    /// Get the number of points that this transform can process.
+    fn len(&self) -> usize;
+
+    /// Convenience method to make an input vector of the right type and length.
+    fn make_input_vec(&self) -> Vec<T>;
+
+    /// Convenience method to make an output vector of the right type and length.
+    fn make_output_vec(&self) -> Vec<T>;
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    fn make_scratch_vec(&self) -> Vec<Complex<T>>;
 }
 
 fn zip3<A, B, C>(a: A, b: B, c: C) -> impl Iterator<Item = (A::Item, B::Item, C::Item)>
@@ -343,12 +734,165 @@ where
         .map(|(x, (y, z))| (x, y, z))
 }
 
+// A planner of complex FFTs, so that the real FFTs can be built using different planners of RustFFT.
+pub(crate) trait ComplexPlanner<T: FftNum> {
+    fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn Fft<T>>;
+    fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn Fft<T>>;
+}
+
+impl<T: FftNum> ComplexPlanner<T> for FftPlanner<T> {
+    fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        FftPlanner::plan_fft_forward(self, len)
+    }
+
+    fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        FftPlanner::plan_fft_inverse(self, len)
+    }
+}
+
+impl<T: FftNum> ComplexPlanner<T> for FftPlannerScalar<T> {
+    fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        FftPlannerScalar::plan_fft_forward(self, len)
+    }
+
+    fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        FftPlannerScalar::plan_fft_inverse(self, len)
+    }
+}
+
+// The default and the scalar complex planners, used for building FFTs with a given choice.
+pub(crate) type Planners<'a, T> = (&'a mut FftPlanner<T>, &'a mut FftPlannerScalar<T>);
+
+// Build a Real-to-Complex forward FFT using the algorithm and the complex planner of the choice.
+pub(crate) fn build_chosen_forward<T: FftNum>(
+    len: usize,
+    choice: Choice,
+    normalization: Normalization,
+    planners: Planners<'_, T>,
+) -> Arc<dyn RealToComplex<T>> {
+    if choice.scalar {
+        build_fft_forward(len, choice.algorithm, normalization, planners.1)
+    } else {
+        build_fft_forward(len, choice.algorithm, normalization, planners.0)
+    }
+}
+
+// Build a Complex-to-Real inverse FFT using the algorithm and the complex planner of the choice.
+pub(crate) fn build_chosen_inverse<T: FftNum>(
+    len: usize,
+    choice: Choice,
+    normalization: Normalization,
+    planners: Planners<'_, T>,
+) -> Arc<dyn ComplexToReal<T>> {
+    if choice.scalar {
+        build_fft_inverse(len, choice.algorithm, normalization, planners.1)
+    } else {
+        build_fft_inverse(len, choice.algorithm, normalization, planners.0)
+    }
+}
+
+// Get the algorithm that is used for a length when nothing else has been chosen.
+pub(crate) fn default_algorithm(len: usize) -> Algorithm {
+    if len % 2 == 0 {
+        Algorithm::HalfLength
+    } else {
+        match odd_radix(len) {
+            1 if len >= MIN_RADER_LEN && is_prime(len) => Algorithm::Rader,
+            1 => Algorithm::FullLength,
+            radix => Algorithm::Split(radix),
+        }
+    }
+}
+
+// Build a Real-to-Complex forward FFT using the given algorithm.
+pub(crate) fn build_fft_forward<T: FftNum, P: ComplexPlanner<T>>(
+    len: usize,
+    algorithm: Algorithm,
+    normalization: Normalization,
+    planner: &mut P,
+) -> Arc<dyn RealToComplex<T>> {
+    match algorithm {
+        Algorithm::HalfLength => {
+            Arc::new(RealToComplexEven::with_planner(len, normalization, planner))
+        }
+        Algorithm::FullLength => {
+            Arc::new(RealToComplexOdd::with_radix(len, 1, normalization, planner))
+        }
+        Algorithm::Split(radix) => Arc::new(RealToComplexOdd::with_radix(
+            len,
+            radix,
+            normalization,
+            planner,
+        )),
+        Algorithm::Rader => Arc::new(RealToComplexRader::new(len, normalization, planner)),
+    }
+}
+
+// Build a Complex-to-Real inverse FFT using the given algorithm.
+pub(crate) fn build_fft_inverse<T: FftNum, P: ComplexPlanner<T>>(
+    len: usize,
+    algorithm: Algorithm,
+    normalization: Normalization,
+    planner: &mut P,
+) -> Arc<dyn ComplexToReal<T>> {
+    match algorithm {
+        Algorithm::HalfLength => {
+            Arc::new(ComplexToRealEven::with_planner(len, normalization, planner))
+        }
+        Algorithm::FullLength => {
+            Arc::new(ComplexToRealOdd::with_radix(len, 1, normalization, planner))
+        }
+        Algorithm::Split(radix) => Arc::new(ComplexToRealOdd::with_radix(
+            len,
+            radix,
+            normalization,
+            planner,
+        )),
+        Algorithm::Rader => Arc::new(ComplexToRealRader::new(len, normalization, planner)),
+    }
+}
+
 /// A planner is used to create FFTs. It caches results internally,
 /// so when making more than one FFT it is advisable to reuse the same planner.
+///
+/// By default the caches grow without limit. A limit can be set with `set_cache_capacity`,
+/// and then the least recently used FFTs are evicted when the limit is exceeded.
+/// FFTs that are evicted or removed from the caches stay valid for as long as they are in use.
+/// The inner complex FFTs are cached by the inner complex planner, which has no limit.
+/// They are shared by all FFTs that need them, also when these are planned again after being evicted,
+/// and they are only released by `clear`.
 pub struct RealFftPlanner<T: FftNum> {
     planner: FftPlanner<T>,
-    r2c_cache: HashMap<usize, Arc<dyn RealToComplex<T>>>,
-    c2r_cache: HashMap<usize, Arc<dyn ComplexToReal<T>>>,
+    scalar_planner: FftPlannerScalar<T>,
+    r2c_cache: LruCache<(usize, Normalization), Arc<dyn RealToComplex<T>>>,
+    c2r_cache: LruCache<(usize, Normalization), Arc<dyn ComplexToReal<T>>>,
+    pair_cache: LruCache<usize, Arc<RealToComplexPair<T>>>,
+    dht_cache: LruCache<usize, Arc<dyn RealToReal<T>>>,
+    wisdom: Wisdom,
+    mode: PlanningMode,
+}
+
+/// Selects how a planner chooses the algorithms for lengths where it has no imported wisdom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanningMode {
+    /// Choose the algorithm from the length alone. This is the default.
+    Estimate,
+    /// Build the FFTs with all the algorithms that can be used for the length, run them,
+    /// and keep the fastest one. This makes planning much slower, and is mostly useful together with wisdom,
+    /// so that the measured choices can be exported and reused.
+    /// The candidates are a complex FFT of the full length for all lengths, packing the data into a complex FFT
+    /// of half the length for even lengths, splitting into 3, 5, 7, 11 or 13 subsequences for odd lengths divisible by these,
+    /// and Rader's algorithm for odd prime lengths.
+    /// On x86_64 and aarch64, where RustFFT uses SIMD instructions, each algorithm is also measured with
+    /// the inner complex FFTs built by the scalar planner of RustFFT.
+    /// When there is only one candidate for the length, it is chosen without measuring.
+    Measure,
+}
+
+// A rough estimate of the memory used by an FFT of the given length, based on the length alone.
+// It assumes that the FFT and its inner complex FFT both store about `len/2` complex twiddle factors.
+fn approximate_footprint<T>(len: usize) -> usize {
+    len * std::mem::size_of::<Complex<T>>()
 }
 
 impl<T: FftNum> RealFftPlanner<T> {
@@ -356,8 +900,13 @@ impl<T: FftNum> RealFftPlanner<T> {
     pub fn new() -> Self {
         let planner = FftPlanner::<T>::new();
         Self {
-            r2c_cache: HashMap::new(),
-            c2r_cache: HashMap::new(),
+            scalar_planner: FftPlannerScalar::new(),
+            r2c_cache: LruCache::new(),
+            c2r_cache: LruCache::new(),
+            pair_cache: LruCache::new(),
+            dht_cache: LruCache::new(),
+            wisdom: Wisdom::new(),
+            mode: PlanningMode::Estimate,
             planner,
         }
     }
@@ -365,16 +914,40 @@ impl<T: FftNum> RealFftPlanner<T> {
     /// Plan a Real-to-Complex forward FFT. Returns the FFT in a shared reference.
     /// If requesting a second FFT of the same length, this will return a new reference to the already existing one.
     pub fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn RealToComplex<T>> {
-        if let Some(fft) = self.r2c_cache.get(&len) {
-            Arc::clone(fft)
+        self.plan_fft_forward_normalized(len, Normalization::None)
+    }
+
+    /// Plan a Real-to-Complex forward FFT that scales the result according to the given normalization.
+    /// Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same length and normalization, this will return a new reference to the already existing one.
+    pub fn plan_fft_forward_normalized(
+        &mut self,
+        len: usize,
+        normalization: Normalization,
+    ) -> Arc<dyn RealToComplex<T>> {
+        if let Some(fft) = self.r2c_cache.get(&(len, normalization)) {
+            fft
         } else {
-            let fft = if len % 2 > 0 {
-                Arc::new(RealToComplexOdd::new(len, &mut self.planner)) as Arc<dyn RealToComplex<T>>
-            } else {
-                Arc::new(RealToComplexEven::new(len, &mut self.planner))
-                    as Arc<dyn RealToComplex<T>>
+            let planners = (&mut self.planner, &mut self.scalar_planner);
+            let fft = match self.wisdom.forward.get(&len) {
+                Some(choice) => build_chosen_forward(len, *choice, normalization, planners),
+                None => match self.mode {
+                    // Estimated choices are not stored, since they are cheap to find again from the length
+                    PlanningMode::Estimate => {
+                        build_chosen_forward(len, Choice::estimate(len), normalization, planners)
+                    }
+                    PlanningMode::Measure => {
+                        let (choice, fft) = fastest_forward(len, normalization, planners);
+                        self.wisdom.forward.insert(len, choice);
+                        fft
+                    }
+                },
             };
-            self.r2c_cache.insert(len, Arc::clone(&fft));
+            self.r2c_cache.insert(
+                (len, normalization),
+                Arc::clone(&fft),
+                approximate_footprint::<T>(len),
+            );
             fft
         }
     }
@@ -382,19 +955,177 @@ impl<T: FftNum> RealFftPlanner<T> {
     /// Plan a Complex-to-Real inverse FFT. Returns the FFT in a shared reference.
     /// If requesting a second FFT of the same length, this will return a new reference to the already existing one.
     pub fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn ComplexToReal<T>> {
-        if let Some(fft) = self.c2r_cache.get(&len) {
-            Arc::clone(fft)
+        self.plan_fft_inverse_normalized(len, Normalization::None)
+    }
+
+    /// Plan a Complex-to-Real inverse FFT that scales the result according to the given normalization.
+    /// Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same length and normalization, this will return a new reference to the already existing one.
+    pub fn plan_fft_inverse_normalized(
+        &mut self,
+        len: usize,
+        normalization: Normalization,
+    ) -> Arc<dyn ComplexToReal<T>> {
+        if let Some(fft) = self.c2r_cache.get(&(len, normalization)) {
+            fft
         } else {
-            let fft = if len % 2 > 0 {
-                Arc::new(ComplexToRealOdd::new(len, &mut self.planner)) as Arc<dyn ComplexToReal<T>>
-            } else {
-                Arc::new(ComplexToRealEven::new(len, &mut self.planner))
-                    as Arc<dyn ComplexToReal<T>>
+            let planners = (&mut self.planner, &mut self.scalar_planner);
+            let fft = match self.wisdom.inverse.get(&len) {
+                Some(choice) => build_chosen_inverse(len, *choice, normalization, planners),
+                None => match self.mode {
+                    PlanningMode::Estimate => {
+                        build_chosen_inverse(len, Choice::estimate(len), normalization, planners)
+                    }
+                    PlanningMode::Measure => {
+                        let (choice, fft) = fastest_inverse(len, normalization, planners);
+                        self.wisdom.inverse.insert(len, choice);
+                        fft
+                    }
+                },
             };
-            self.c2r_cache.insert(len, Arc::clone(&fft));
+            self.c2r_cache.insert(
+                (len, normalization),
+                Arc::clone(&fft),
+                approximate_footprint::<T>(len),
+            );
+            fft
+        }
+    }
+
+    /// Plan a Real-to-Complex forward FFT that transforms two signals at once. Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same length, this will return a new reference to the already existing one.
+    pub fn plan_fft_forward_pair(&mut self, len: usize) -> Arc<RealToComplexPair<T>> {
+        if let Some(fft) = self.pair_cache.get(&len) {
+            fft
+        } else {
+            let fft = Arc::new(RealToComplexPair::new(len, &mut self.planner));
+            self.pair_cache
+                .insert(len, Arc::clone(&fft), approximate_footprint::<T>(len));
             fft
         }
     }
+
+    /// Plan a Discrete Hartley Transform. Returns the transform in a shared reference.
+    /// The DHT is its own inverse, apart from scaling, so the same transform is used in both directions.
+    /// If requesting a second DHT of the same length, this will return a new reference to the already existing one.
+    pub fn plan_dht(&mut self, len: usize) -> Arc<dyn RealToReal<T>> {
+        if let Some(dht) = self.dht_cache.get(&len) {
+            dht
+        } else {
+            let dht = Arc::new(Dht::new(len, self)) as Arc<dyn RealToReal<T>>;
+            // Count the wrapped forward FFT and the spectrum that the DHT needs on top of it
+            let footprint =
+                approximate_footprint::<T>(len) + (len / 2 + 1) * std::mem::size_of::<Complex<T>>();
+            self.dht_cache.insert(len, Arc::clone(&dht), footprint);
+            dht
+        }
+    }
+
+    /// Limit the number of FFTs that are stored in each of the caches, or remove the limit with `None`.
+    /// When the limit is exceeded, the least recently planned or requested FFTs are evicted.
+    pub fn set_cache_capacity(&mut self, capacity: Option<usize>) {
+        self.r2c_cache.set_capacity(capacity);
+        self.c2r_cache.set_capacity(capacity);
+        self.pair_cache.set_capacity(capacity);
+        self.dht_cache.set_capacity(capacity);
+    }
+
+    /// Remove all FFTs from the caches, including the inner complex FFTs.
+    /// FFTs planned after this don't share any inner FFTs with the ones planned before.
+    pub fn clear(&mut self) {
+        self.r2c_cache.clear();
+        self.c2r_cache.clear();
+        self.pair_cache.clear();
+        self.dht_cache.clear();
+        self.planner = FftPlanner::new();
+        self.scalar_planner = FftPlannerScalar::new();
+    }
+
+    /// Remove all FFTs of the given length from the caches, for any normalization.
+    pub fn remove(&mut self, len: usize) {
+        self.r2c_cache.remove_matching(|key| key.0 == len);
+        self.c2r_cache.remove_matching(|key| key.0 == len);
+        self.pair_cache.remove_matching(|key| *key == len);
+        self.dht_cache.remove_matching(|key| *key == len);
+    }
+
+    /// Get the sorted lengths of all FFTs in the caches.
+    pub fn cached_lengths(&self) -> Vec<usize> {
+        let mut lengths: Vec<usize> = self
+            .r2c_cache
+            .keys()
+            .map(|key| key.0)
+            .chain(self.c2r_cache.keys().map(|key| key.0))
+            .chain(self.pair_cache.keys())
+            .chain(self.dht_cache.keys())
+            .collect();
+        lengths.sort_unstable();
+        lengths.dedup();
+        lengths
+    }
+
+    /// Get a rough estimate of the number of bytes used by the FFTs in the caches.
+    /// The estimate is based on the lengths alone, and counts `len` complex values for each FFT.
+    /// Each DHT also counts the `len / 2 + 1` complex values of its spectrum.
+    /// It is not measured from the actual twiddle factors, and it does not include
+    /// the inner complex FFTs kept by the inner planner after eviction, or any other scratch space.
+    pub fn cache_footprint(&self) -> usize {
+        self.r2c_cache.footprint()
+            + self.c2r_cache.footprint()
+            + self.pair_cache.footprint()
+            + self.dht_cache.footprint()
+    }
+
+    /// Set how the algorithms are chosen for lengths where the planner has no imported or measured wisdom.
+    /// This only affects FFTs that are planned later, and FFTs that are already in the caches are not re-planned.
+    pub fn set_planning_mode(&mut self, mode: PlanningMode) {
+        self.mode = mode;
+    }
+
+    /// Export the algorithms that were measured or imported so far, as text.
+    /// Algorithms that are estimated from the length alone are not included, since all planners choose the same ones.
+    /// The text can be imported by another planner with `import_wisdom`, to make it use the same algorithms.
+    ///
+    /// The format has a header line, followed by one line per FFT with the direction, the length and the algorithm.
+    /// The algorithm is followed by `scalar` when the inner complex FFTs are built by the scalar planner of RustFFT.
+    /// For example:
+    /// ```text
+    /// realfft-wisdom 1
+    /// forward 1024 half
+    /// forward 6075 split 3
+    /// inverse 1001 full scalar
+    /// ```
+    pub fn export_wisdom(&self) -> String {
+        self.wisdom.export()
+    }
+
+    /// Import algorithms exported by `export_wisdom`. These are then used when planning FFTs of the given lengths.
+    /// Cached FFTs and DHTs that were planned with different algorithms are removed from the caches.
+    /// Nothing is imported if any line of the text is invalid.
+    pub fn import_wisdom(&mut self, wisdom: &str) -> Result<(), WisdomError> {
+        let imported = Wisdom::parse(wisdom)?;
+        for (len, choice) in imported.forward.into_iter() {
+            let previous = self.wisdom.forward.insert(len, choice);
+            // FFTs without previous wisdom were planned with the estimated choice
+            if previous.unwrap_or_else(|| Choice::estimate(len)) != choice {
+                self.r2c_cache.remove_matching(|key| key.0 == len);
+                // The DHTs wrap a forward FFT
+                self.dht_cache.remove_matching(|key| *key == len);
+            }
+        }
+        for (len, choice) in imported.inverse.into_iter() {
+            let previous = self.wisdom.inverse.insert(len, choice);
+            if previous.unwrap_or_else(|| Choice::estimate(len)) != choice {
+                self.c2r_cache.remove_matching(|key| key.0 == len);
+            }
+        }
+        Ok(())
+    }
+
+    // Get the inner complex planner, for planning complex FFTs that share its cache.
+    pub(crate) fn complex_planner(&mut self) -> &mut FftPlanner<T> {
+        &mut self.planner
+    }
 }
 
 impl<T: FftNum> Default for RealFftPlanner<T> {
@@ -403,21 +1134,241 @@ impl<T: FftNum> Default for RealFftPlanner<T> {
     }
 }
 
+/// Find the shortest length of at least `len` that can be transformed efficiently by a real FFT,
+/// for example when choosing how much to zero-pad a signal.
+///
+/// Even lengths use a complex FFT of half the length, so the returned length is even,
+/// and half of it only has the prime factors 2, 3 and 5.
+/// The shortest returned length is 2.
+/// Panics if there is no such length that fits in a `usize`.
+pub fn next_fast_len(len: usize) -> usize {
+    let overflow = "No fast length that fits in a usize";
+    let mut candidate = len.checked_add(len % 2).expect(overflow).max(2);
+    while !is_smooth(candidate / 2) {
+        candidate = candidate.checked_add(2).expect(overflow);
+    }
+    candidate
+}
+
+// Check if a length only has the prime factors 2, 3 and 5, which are the fastest lengths for complex FFTs.
+pub(crate) fn is_smooth(len: usize) -> bool {
+    has_only_factors(len, &[2, 3, 5])
+}
+
+// Check if a length is a product of the given prime factors.
+pub(crate) fn has_only_factors(len: usize, factors: &[usize]) -> bool {
+    if len == 0 {
+        return false;
+    }
+    let mut rest = len;
+    for factor in factors.iter() {
+        while rest % factor == 0 {
+            rest /= factor;
+        }
+    }
+    rest == 1
+}
+
 impl<T: FftNum> RealToComplexOdd<T> {
     /// Create a new RealToComplex FFT for input data of a given length, and uses the given FftPlanner to build the inner FFT.
     /// Panics if the length is not odd.
+    ///
+    /// Inputs where the length is divisible by `p` = 3, 5, 7, 11 or 13 are split into `p` interleaved subsequences,
+    /// when the length is long enough or N/p has a prime factor larger than 7, see the crate documentation.
+    /// These are transformed pairwise by a complex FFT of length N/p, and the results are then combined.
+    /// Other lengths are transformed using a complex FFT of the full length.
     pub fn new(length: usize, fft_planner: &mut FftPlanner<T>) -> Self {
+        Self::new_with_normalization(length, Normalization::None, fft_planner)
+    }
+
+    /// Create a new RealToComplex FFT for input data of a given length, that scales the result according to the given normalization.
+    /// Uses the given FftPlanner to build the inner FFT.
+    /// Panics if the length is not odd.
+    pub fn new_with_normalization(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut FftPlanner<T>,
+    ) -> Self {
         if length % 2 == 0 {
             panic!("Length must be odd, got {}", length,);
         }
-        let fft = fft_planner.plan_fft_forward(length);
-        let scratch_len = fft.get_inplace_scratch_len() + length;
+        Self::with_radix(length, odd_radix(length), normalization, fft_planner)
+    }
+
+    // Create a new FFT that splits the data into `radix` subsequences, where a radix of 1 means no splitting.
+    // Without splitting, even lengths can also be transformed.
+    pub(crate) fn with_radix<P: ComplexPlanner<T>>(
+        length: usize,
+        radix: usize,
+        normalization: Normalization,
+        fft_planner: &mut P,
+    ) -> Self {
+        if length % 2 == 0 && radix > 1 {
+            panic!("Length must be odd, got {}", length,);
+        }
+        let scale = normalization.forward_scale(length);
+        if radix == 1 {
+            let fft = fft_planner.plan_fft_forward(length);
+            let scratch_len = fft.get_inplace_scratch_len() + length;
+            return RealToComplexOdd {
+                twiddles: Vec::new(),
+                radix_twiddles: Vec::new(),
+                length,
+                radix,
+                fft,
+                inner_r2c: None,
+                scale,
+                scratch_len,
+            };
+        }
+        // Split the input in `radix` interleaved subsequences of length `inner_len`.
+        // All but the last are transformed pairwise by packing them in the real and imaginary parts of a complex FFT,
+        // and the last one is transformed by a real FFT of the inner length.
+        let inner_len = length / radix;
+        let twiddles: Vec<Complex<T>> = (0..inner_len / 2 + 1)
+            .flat_map(|idx| (1..radix).map(move |r| compute_twiddle::<T>(r * idx, length) * scale))
+            .collect();
+        let radix_twiddles = compute_radix_twiddles(radix);
+        let fft = fft_planner.plan_fft_forward(inner_len);
+        let inner_r2c = build_fft_forward(
+            inner_len,
+            default_algorithm(inner_len),
+            Normalization::None,
+            fft_planner,
+        );
+        let inner_scratch_len = fft
+            .get_inplace_scratch_len()
+            .max(inner_r2c.get_scratch_len());
+        let scratch_len = (radix - 1) / 2 * inner_len + inner_len / 2 + 1 + inner_scratch_len;
         RealToComplexOdd {
+            twiddles,
+            radix_twiddles,
             length,
+            radix,
             fft,
+            inner_r2c: Some(inner_r2c),
+            scale,
             scratch_len,
         }
     }
+
+    // Transform the input as a complex vector using a FFT of the full length.
+    fn process_complex(&self, input: &[T], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        let (buffer, fft_scratch) = scratch.split_at_mut(self.length);
+
+        for (val, buf) in input.iter().zip(buffer.iter_mut()) {
+            *buf = Complex::new(*val, T::zero());
+        }
+        // FFT and store result in buffer_out
+        self.fft.process_with_scratch(buffer, fft_scratch);
+        for (out, val) in output.iter_mut().zip(buffer.iter()) {
+            *out = *val * self.scale;
+        }
+    }
+
+    // Transform the input by splitting it into interleaved subsequences.
+    fn process_split(
+        &self,
+        input: &mut [T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let radix = self.radix;
+        let inner_len = self.length / radix;
+        let half_radix = (radix - 1) / 2;
+        let (pairs, rest) = scratch.split_at_mut(half_radix * inner_len);
+        let (last, inner_scratch) = rest.split_at_mut(inner_len / 2 + 1);
+
+        // Pack the subsequences pairwise into the real and imaginary parts of complex vectors
+        for (chunk_idx, chunk) in pairs.chunks_mut(inner_len).enumerate() {
+            let first = 2 * chunk_idx;
+            for (buf, vals) in chunk.iter_mut().zip(input.chunks(radix)) {
+                *buf = Complex::new(vals[first], vals[first + 1]);
+            }
+        }
+        // Gather the last subsequence at the start of the input, which is then used as input for the inner real FFT
+        for idx in 0..inner_len {
+            input[idx] = input[radix * idx + radix - 1];
+        }
+        self.fft.process_with_scratch(pairs, inner_scratch);
+        if let Some(inner_r2c) = &self.inner_r2c {
+            inner_r2c.process_with_scratch(&mut input[0..inner_len], last, inner_scratch)?;
+        }
+
+        // Separate the spectra of the pairs of subsequences, apply twiddle factors,
+        // and combine them with a DFT of length `radix`.
+        // Only the first half of each subsequence spectrum is used. The outputs from the first half of the DFT
+        // end up in the first half of the result, and the ones from the second half are stored
+        // as the complex conjugates of the mirrored values.
+        for (idx, (twiddles, last_value)) in self
+            .twiddles
+            .chunks_exact(radix - 1)
+            .zip(last.iter())
+            .enumerate()
+        {
+            let idx_rev = if idx == 0 { 0 } else { inner_len - idx };
+            // The twiddle factors include the scaling, only the first value needs to be scaled separately
+            let (first, second) = split_pair(pairs[idx], pairs[idx_rev]);
+            let first = first * self.scale;
+            if radix == 3 {
+                let values = dft3(
+                    first,
+                    second * twiddles[0],
+                    *last_value * twiddles[1],
+                    self.radix_twiddles[0].im,
+                );
+                output[idx] = values[0];
+                output[idx + inner_len] = values[1];
+                output[inner_len - idx] = values[2].conj();
+            } else if radix == 5 {
+                let (third, fourth) =
+                    split_pair(pairs[inner_len + idx], pairs[inner_len + idx_rev]);
+                let values = dft5(
+                    [
+                        first,
+                        second * twiddles[0],
+                        third * twiddles[1],
+                        fourth * twiddles[2],
+                        *last_value * twiddles[3],
+                    ],
+                    &self.radix_twiddles,
+                );
+                output[idx] = values[0];
+                output[idx + inner_len] = values[1];
+                output[idx + 2 * inner_len] = values[2];
+                output[2 * inner_len - idx] = values[3].conj();
+                output[inner_len - idx] = values[4].conj();
+            } else {
+                let mut values = [Complex::zero(); MAX_SPLIT_RADIX];
+                let mut transformed = [Complex::zero(); MAX_SPLIT_RADIX];
+                values[0] = first;
+                values[1] = second * twiddles[0];
+                for pair in 1..half_radix {
+                    let (first, second) = split_pair(
+                        pairs[pair * inner_len + idx],
+                        pairs[pair * inner_len + idx_rev],
+                    );
+                    values[2 * pair] = first * twiddles[2 * pair - 1];
+                    values[2 * pair + 1] = second * twiddles[2 * pair];
+                }
+                values[radix - 1] = *last_value * twiddles[radix - 2];
+                dft_odd(
+                    &values[..radix],
+                    &self.radix_twiddles,
+                    &mut transformed[..radix],
+                );
+                // Store the outputs beyond the first half as the complex conjugates of the mirrored values
+                for (r, value) in transformed[..radix].iter().enumerate() {
+                    if 2 * r < radix {
+                        output[idx + r * inner_len] = *value;
+                    } else {
+                        output[(radix - r) * inner_len - idx] = value.conj();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T: FftNum> RealToComplex<T> for RealToComplexOdd<T> {
@@ -453,15 +1404,12 @@ impl<T: FftNum> RealToComplex<T> for RealToComplexOdd<T> {
         if scratch.len() < (self.scratch_len) {
             return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
         }
-        let (buffer, fft_scratch) = scratch.split_at_mut(self.length);
-
-        for (val, buf) in input.iter().zip(buffer.iter_mut()) {
-            *buf = Complex::new(*val, T::zero());
+        if self.radix == 1 {
+            self.process_complex(input, output, scratch);
+            Ok(())
+        } else {
+            self.process_split(input, output, scratch)
         }
-        // FFT and store result in buffer_out
-        self.fft.process_with_scratch(buffer, fft_scratch);
-        output.copy_from_slice(&buffer[0..self.length / 2 + 1]);
-        Ok(())
     }
 
     fn get_scratch_len(&self) -> usize {
@@ -489,16 +1437,38 @@ impl<T: FftNum> RealToComplexEven<T> {
     /// Create a new RealToComplex FFT for input data of a given length, and uses the given FftPlanner to build the inner FFT.
     /// Panics if the length is not even.
     pub fn new(length: usize, fft_planner: &mut FftPlanner<T>) -> Self {
+        Self::new_with_normalization(length, Normalization::None, fft_planner)
+    }
+
+    /// Create a new RealToComplex FFT for input data of a given length, that scales the result according to the given normalization.
+    /// The scaling is included in the twiddle factors of the post-processing.
+    /// Uses the given FftPlanner to build the inner FFT.
+    /// Panics if the length is not even.
+    pub fn new_with_normalization(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut FftPlanner<T>,
+    ) -> Self {
+        Self::with_planner(length, normalization, fft_planner)
+    }
+
+    // Create a new FFT that uses any complex planner to build the inner FFT.
+    pub(crate) fn with_planner<P: ComplexPlanner<T>>(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut P,
+    ) -> Self {
         if length % 2 > 0 {
             panic!("Length must be even, got {}", length,);
         }
+        let scale = normalization.forward_scale::<T>(length);
         let twiddle_count = if length % 4 == 0 {
             length / 4
         } else {
             length / 4 + 1
         };
         let twiddles: Vec<Complex<T>> = (1..twiddle_count)
-            .map(|i| compute_twiddle(i, length) * T::from_f64(0.5).unwrap())
+            .map(|i| compute_twiddle(i, length) * T::from_f64(0.5).unwrap() * scale)
             .collect();
         //let mut fft_planner = FftPlanner::<T>::new();
         let fft = fft_planner.plan_fft_forward(length / 2);
@@ -507,6 +1477,7 @@ impl<T: FftNum> RealToComplexEven<T> {
             twiddles,
             length,
             fft,
+            scale,
             scratch_len,
         }
     }
@@ -564,11 +1535,11 @@ impl<T: FftNum> RealToComplex<T> for RealToComplexEven<T> {
                 // The first and last elements are just a sum and difference of the first value's real and imaginary values
                 let first_value = *first_element;
                 *first_element = Complex {
-                    re: first_value.re + first_value.im,
+                    re: (first_value.re + first_value.im) * self.scale,
                     im: T::zero(),
                 };
                 *last_element = Complex {
-                    re: first_value.re - first_value.im,
+                    re: (first_value.re - first_value.im) * self.scale,
                     im: T::zero(),
                 };
 
@@ -582,6 +1553,7 @@ impl<T: FftNum> RealToComplex<T> for RealToComplexEven<T> {
             }
         }
         // Loop over the remaining elements and apply twiddle factors on them
+        let half = T::from_f64(0.5).unwrap() * self.scale;
         for (twiddle, out, out_rev) in zip3(
             self.twiddles.iter(),
             output_left.iter_mut(),
@@ -589,7 +1561,6 @@ impl<T: FftNum> RealToComplex<T> for RealToComplexEven<T> {
         ) {
             let sum = *out + *out_rev;
             let diff = *out - *out_rev;
-            let half = T::from_f64(0.5).unwrap();
             // Apply twiddle factors. Theoretically we'd have to load 2 separate twiddle factors here, one for the beginning
             // and one for the end. But the twiddle factor for the end is just the twiddle for the beginning, with the
             // real part negated. Since it's the same twiddle, we can factor out a ton of math ops and cut the number of
@@ -619,7 +1590,7 @@ impl<T: FftNum> RealToComplex<T> for RealToComplexEven<T> {
         // If the output len is odd, the loop above can't postprocess the centermost element, so handle that separately.
         if output.len() % 2 == 1 {
             if let Some(center_element) = output.get_mut(output.len() / 2) {
-                center_element.im = -center_element.im;
+                *center_element = center_element.conj() * self.scale;
             }
         }
         Ok(())
@@ -648,19 +1619,217 @@ impl<T: FftNum> RealToComplex<T> for RealToComplexEven<T> {
 impl<T: FftNum> ComplexToRealOdd<T> {
     /// Create a new ComplexToReal FFT for input data of a given length, and uses the given FftPlanner to build the inner FFT.
     /// Panics if the length is not odd.
+    ///
+    /// Outputs where the length is divisible by `p` = 3, 5, 7, 11 or 13 are calculated as `p` interleaved subsequences,
+    /// when the length is long enough or N/p has a prime factor larger than 7, see the crate documentation.
+    /// The spectrum is split into the spectra of these subsequences, which are then transformed pairwise
+    /// by a complex iFFT of length N/p.
+    /// Other lengths are transformed using a complex iFFT of the full length.
     pub fn new(length: usize, fft_planner: &mut FftPlanner<T>) -> Self {
+        Self::new_with_normalization(length, Normalization::None, fft_planner)
+    }
+
+    /// Create a new ComplexToReal FFT for output data of a given length, that scales the result according to the given normalization.
+    /// Uses the given FftPlanner to build the inner FFT.
+    /// Panics if the length is not odd.
+    pub fn new_with_normalization(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut FftPlanner<T>,
+    ) -> Self {
         if length % 2 == 0 {
             panic!("Length must be odd, got {}", length,);
         }
-        //let mut fft_planner = FftPlanner::<T>::new();
-        let fft = fft_planner.plan_fft_inverse(length);
-        let scratch_len = length + fft.get_inplace_scratch_len();
+        Self::with_radix(length, odd_radix(length), normalization, fft_planner)
+    }
+
+    // Create a new FFT that splits the data into `radix` subsequences, where a radix of 1 means no splitting.
+    // Without splitting, even lengths can also be transformed.
+    pub(crate) fn with_radix<P: ComplexPlanner<T>>(
+        length: usize,
+        radix: usize,
+        normalization: Normalization,
+        fft_planner: &mut P,
+    ) -> Self {
+        if length % 2 == 0 && radix > 1 {
+            panic!("Length must be odd, got {}", length,);
+        }
+        let scale = normalization.inverse_scale(length);
+        if radix == 1 {
+            let fft = fft_planner.plan_fft_inverse(length);
+            let scratch_len = length + fft.get_inplace_scratch_len();
+            return ComplexToRealOdd {
+                twiddles: Vec::new(),
+                radix_twiddles: Vec::new(),
+                length,
+                radix,
+                fft,
+                inner_c2r: None,
+                scale,
+                scratch_len,
+            };
+        }
+        // Calculate the output as `radix` interleaved subsequences of length `inner_len`.
+        // All but the last are transformed pairwise by packing their spectra into a complex iFFT,
+        // and the last one is transformed by a real iFFT of the inner length.
+        let inner_len = length / radix;
+        let twiddles: Vec<Complex<T>> = (0..inner_len / 2 + 1)
+            .flat_map(|idx| {
+                (1..radix).map(move |r| compute_twiddle::<T>(r * idx, length).conj() * scale)
+            })
+            .collect();
+        let radix_twiddles: Vec<Complex<T>> = compute_radix_twiddles(radix)
+            .iter()
+            .map(|twiddle: &Complex<T>| twiddle.conj())
+            .collect();
+        let fft = fft_planner.plan_fft_inverse(inner_len);
+        let inner_c2r = build_fft_inverse(
+            inner_len,
+            default_algorithm(inner_len),
+            Normalization::None,
+            fft_planner,
+        );
+        let inner_scratch_len = fft
+            .get_inplace_scratch_len()
+            .max(inner_c2r.get_scratch_len());
+        let scratch_len = (radix - 1) / 2 * inner_len + inner_len / 2 + 1 + inner_scratch_len;
         ComplexToRealOdd {
+            twiddles,
+            radix_twiddles,
             length,
+            radix,
             fft,
+            inner_c2r: Some(inner_c2r),
+            scale,
             scratch_len,
         }
     }
+
+    // Transform the input by building the full spectrum and using a complex iFFT of the full length.
+    fn process_complex(&self, input: &[Complex<T>], output: &mut [T], scratch: &mut [Complex<T>]) {
+        let (buffer, fft_scratch) = scratch.split_at_mut(self.length);
+
+        buffer[0..input.len()].copy_from_slice(input);
+        for (buf, val) in buffer
+            .iter_mut()
+            .rev()
+            .take((self.length - 1) / 2)
+            .zip(input.iter().skip(1))
+        {
+            *buf = val.conj();
+        }
+        self.fft.process_with_scratch(buffer, fft_scratch);
+        for (val, out) in buffer.iter().zip(output.iter_mut()) {
+            *out = val.re * self.scale;
+        }
+    }
+
+    // Transform the input by splitting it into the spectra of interleaved subsequences.
+    fn process_split(
+        &self,
+        input: &[Complex<T>],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let radix = self.radix;
+        let inner_len = self.length / radix;
+        let half_radix = (radix - 1) / 2;
+        let (pairs, rest) = scratch.split_at_mut(half_radix * inner_len);
+        let (last, inner_scratch) = rest.split_at_mut(inner_len / 2 + 1);
+
+        // Gather the values of the full spectrum that contribute to each index of the subsequence spectra,
+        // taking values beyond the first half as the complex conjugates of the mirrored values.
+        // Split them with an inverse DFT of length `radix`, apply twiddle factors that include the scaling,
+        // and pack the resulting spectra pairwise into the real and imaginary parts of complex spectra.
+        for (idx, (twiddles, last_value)) in self
+            .twiddles
+            .chunks_exact(radix - 1)
+            .zip(last.iter_mut())
+            .enumerate()
+        {
+            let idx_rev = if idx == 0 { 0 } else { inner_len - idx };
+            if radix == 3 {
+                let values = dft3(
+                    input[idx],
+                    input[idx + inner_len],
+                    input[inner_len - idx].conj(),
+                    self.radix_twiddles[0].im,
+                );
+                let (packed, packed_rev) =
+                    join_pair(values[0] * self.scale, values[1] * twiddles[0]);
+                pairs[idx] = packed;
+                pairs[idx_rev] = packed_rev;
+                *last_value = values[2] * twiddles[1];
+            } else if radix == 5 {
+                let values = dft5(
+                    [
+                        input[idx],
+                        input[idx + inner_len],
+                        input[idx + 2 * inner_len],
+                        input[2 * inner_len - idx].conj(),
+                        input[inner_len - idx].conj(),
+                    ],
+                    &self.radix_twiddles,
+                );
+                let (packed, packed_rev) =
+                    join_pair(values[0] * self.scale, values[1] * twiddles[0]);
+                pairs[idx] = packed;
+                pairs[idx_rev] = packed_rev;
+                let (packed, packed_rev) =
+                    join_pair(values[2] * twiddles[1], values[3] * twiddles[2]);
+                pairs[inner_len + idx] = packed;
+                pairs[inner_len + idx_rev] = packed_rev;
+                *last_value = values[4] * twiddles[3];
+            } else {
+                let mut values = [Complex::zero(); MAX_SPLIT_RADIX];
+                let mut transformed = [Complex::zero(); MAX_SPLIT_RADIX];
+                for (r, value) in values[..radix].iter_mut().enumerate() {
+                    *value = if 2 * r < radix {
+                        input[idx + r * inner_len]
+                    } else {
+                        input[(radix - r) * inner_len - idx].conj()
+                    };
+                }
+                dft_odd(
+                    &values[..radix],
+                    &self.radix_twiddles,
+                    &mut transformed[..radix],
+                );
+                let (packed, packed_rev) =
+                    join_pair(transformed[0] * self.scale, transformed[1] * twiddles[0]);
+                pairs[idx] = packed;
+                pairs[idx_rev] = packed_rev;
+                for pair in 1..half_radix {
+                    let (packed, packed_rev) = join_pair(
+                        transformed[2 * pair] * twiddles[2 * pair - 1],
+                        transformed[2 * pair + 1] * twiddles[2 * pair],
+                    );
+                    pairs[pair * inner_len + idx] = packed;
+                    pairs[pair * inner_len + idx_rev] = packed_rev;
+                }
+                *last_value = transformed[radix - 1] * twiddles[radix - 2];
+            }
+        }
+        // The first value of the last spectrum is real, apart from rounding errors.
+        last[0].im = T::zero();
+
+        self.fft.process_with_scratch(pairs, inner_scratch);
+        if let Some(inner_c2r) = &self.inner_c2r {
+            inner_c2r.process_with_scratch(last, &mut output[0..inner_len], inner_scratch)?;
+        }
+        // Spread out the last subsequence, starting from the end to avoid overwriting values that are still needed.
+        for idx in (0..inner_len).rev() {
+            output[radix * idx + radix - 1] = output[idx];
+        }
+        for (chunk_idx, chunk) in pairs.chunks_exact(inner_len).enumerate() {
+            let first = 2 * chunk_idx;
+            for (vals, buf) in output.chunks_exact_mut(radix).zip(chunk.iter()) {
+                vals[first] = buf.re;
+                vals[first + 1] = buf.im;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T: FftNum> ComplexToReal<T> for ComplexToRealOdd<T> {
@@ -709,25 +1878,22 @@ impl<T: FftNum> ComplexToReal<T> for ComplexToRealOdd<T> {
         } else {
             false
         };
+        // The last value is only real for even lengths, which are never split
+        let last = input.len() - 1;
+        let last_invalid = if self.length % 2 == 0 && input[last].im != T::from_f64(0.0).unwrap() {
+            input[last].im = T::from_f64(0.0).unwrap();
+            true
+        } else {
+            false
+        };
 
-        let (buffer, fft_scratch) = scratch.split_at_mut(self.length);
-
-        buffer[0..input.len()].copy_from_slice(input);
-        for (buf, val) in buffer
-            .iter_mut()
-            .rev()
-            .take(self.length / 2)
-            .zip(input.iter().skip(1))
-        {
-            *buf = val.conj();
-            //buf.im = -val.im;
-        }
-        self.fft.process_with_scratch(buffer, fft_scratch);
-        for (val, out) in buffer.iter().zip(output.iter_mut()) {
-            *out = val.re;
+        if self.radix == 1 {
+            self.process_complex(input, output, scratch);
+        } else {
+            self.process_split(input, output, scratch)?;
         }
-        if first_invalid {
-            return Err(FftError::InputValues(true, false));
+        if first_invalid || last_invalid {
+            return Err(FftError::InputValues(first_invalid, last_invalid));
         }
         Ok(())
     }
@@ -757,9 +1923,31 @@ impl<T: FftNum> ComplexToRealEven<T> {
     /// Create a new ComplexToReal FFT for input data of a given length, and uses the given FftPlanner to build the inner FFT.
     /// Panics if the length is not even.
     pub fn new(length: usize, fft_planner: &mut FftPlanner<T>) -> Self {
+        Self::new_with_normalization(length, Normalization::None, fft_planner)
+    }
+
+    /// Create a new ComplexToReal FFT for output data of a given length, that scales the result according to the given normalization.
+    /// The scaling is included in the pre-processing of the input.
+    /// Uses the given FftPlanner to build the inner FFT.
+    /// Panics if the length is not even.
+    pub fn new_with_normalization(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut FftPlanner<T>,
+    ) -> Self {
+        Self::with_planner(length, normalization, fft_planner)
+    }
+
+    // Create a new FFT that uses any complex planner to build the inner FFT.
+    pub(crate) fn with_planner<P: ComplexPlanner<T>>(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut P,
+    ) -> Self {
         if length % 2 > 0 {
             panic!("Length must be even, got {}", length,);
         }
+        let scale = normalization.inverse_scale(length);
         let twiddle_count = if length % 4 == 0 {
             length / 4
         } else {
@@ -775,6 +1963,7 @@ impl<T: FftNum> ComplexToRealEven<T> {
             twiddles,
             length,
             fft,
+            scale,
             scratch_len,
         }
     }
@@ -840,8 +2029,8 @@ impl<T: FftNum> ComplexToReal<T> for ComplexToRealEven<T> {
         // The first and centermost values have to be preprocessed separately from the rest, so do that now.
         match (input_left.first_mut(), input_right.last_mut()) {
             (Some(first_input), Some(last_input)) => {
-                let first_sum = *first_input + *last_input;
-                let first_diff = *first_input - *last_input;
+                let first_sum = (*first_input + *last_input) * self.scale;
+                let first_diff = (*first_input - *last_input) * self.scale;
 
                 *first_input = Complex {
                     re: first_sum.re - first_sum.im,
@@ -861,8 +2050,9 @@ impl<T: FftNum> ComplexToReal<T> for ComplexToRealEven<T> {
             input_left.iter_mut(),
             input_right.iter_mut().rev(),
         ) {
-            let sum = *fft_input + *fft_input_rev;
-            let diff = *fft_input - *fft_input_rev;
+            // Include the scaling in the sum and difference, which the rest of the preprocessing is linear in
+            let sum = (*fft_input + *fft_input_rev) * self.scale;
+            let diff = (*fft_input - *fft_input_rev) * self.scale;
 
             // Apply twiddle factors. Theoretically we'd have to load 2 separate twiddle factors here, one for the beginning
             // and one for the end. But the twiddle factor for the end is just the twiddle for the beginning, with the
@@ -890,7 +2080,7 @@ impl<T: FftNum> ComplexToReal<T> for ComplexToRealEven<T> {
         // If the output len is odd, the loop above can't preprocess the centermost element, so handle that separately
         if input.len() % 2 == 1 {
             let center_element = input[input.len() / 2];
-            let doubled = center_element + center_element;
+            let doubled = (center_element + center_element) * self.scale;
             input[input.len() / 2] = doubled.conj();
         }
 
@@ -929,14 +2119,117 @@ impl<T: FftNum> ComplexToReal<T> for ComplexToRealEven<T> {
     }
 }
 
+impl<T: FftNum> RealToComplexPair<T> {
+    /// Create a new RealToComplexPair FFT for input data of a given length, and uses the given FftPlanner to build the inner FFT.
+    pub fn new(length: usize, fft_planner: &mut FftPlanner<T>) -> Self {
+        let fft = fft_planner.plan_fft_forward(length);
+        let scratch_len = fft.get_inplace_scratch_len() + length;
+        RealToComplexPair {
+            length,
+            fft,
+            scratch_len,
+        }
+    }
+
+    /// Transform two vectors of N real-valued samples, storing the results in two N/2+1 (with N/2 rounded down) element long complex output vectors.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process(
+        &self,
+        input_a: &[T],
+        input_b: &[T],
+        output_a: &mut [Complex<T>],
+        output_b: &mut [Complex<T>],
+    ) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input_a, input_b, output_a, output_b, &mut scratch)
+    }
+
+    /// Transform two vectors of N real-valued samples, storing the results in two N/2+1 (with N/2 rounded down) element long complex output vectors.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input_a: &[T],
+        input_b: &[T],
+        output_a: &mut [Complex<T>],
+        output_b: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        for input in [input_a, input_b].iter() {
+            if input.len() != self.length {
+                return Err(FftError::InputBuffer(self.length, input.len()));
+            }
+        }
+        let expected_output_buffer_size = self.length / 2 + 1;
+        for output in [&output_a, &output_b].iter() {
+            if output.len() != expected_output_buffer_size {
+                return Err(FftError::OutputBuffer(
+                    expected_output_buffer_size,
+                    output.len(),
+                ));
+            }
+        }
+        if scratch.len() < (self.scratch_len) {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let (buffer, fft_scratch) = scratch.split_at_mut(self.length);
+
+        // Pack the first signal in the real part and the second in the imaginary part
+        for (buf, (val_a, val_b)) in buffer.iter_mut().zip(input_a.iter().zip(input_b.iter())) {
+            *buf = Complex::new(*val_a, *val_b);
+        }
+        self.fft.process_with_scratch(buffer, fft_scratch);
+
+        // Separate the two spectra using the values at index k and N-k
+        for (idx, (out_a, out_b)) in output_a.iter_mut().zip(output_b.iter_mut()).enumerate() {
+            let idx_rev = if idx == 0 { 0 } else { self.length - idx };
+            let (value_a, value_b) = split_pair(buffer[idx], buffer[idx_rev]);
+            *out_a = value_a;
+            *out_b = value_b;
+        }
+        Ok(())
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Get the number of points that this FFT can process.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.len() / 2 + 1]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::next_fast_len;
     use crate::FftError;
+    use crate::Normalization;
     use crate::RealFftPlanner;
+    use crate::{build_fft_forward, build_fft_inverse, default_algorithm};
+    use crate::{Algorithm, ComplexToReal, ComplexToRealOdd, RealToComplex, RealToComplexOdd};
     use rand::Rng;
     use rustfft::num_complex::Complex;
     use rustfft::num_traits::Zero;
-    use rustfft::FftPlanner;
+    use rustfft::{Fft, FftPlanner};
     use std::error::Error;
 
     // get the largest difference
@@ -1012,6 +2305,257 @@ mod tests {
         }
     }
 
+    // Compare ComplexToReal with standard iFFT for odd lengths that are split into subsequences,
+    // and for prime lengths that use Rader's algorithm
+    #[test]
+    fn complex_to_real_odd_split() {
+        for length in [
+            37,
+            47,
+            169,
+            221,
+            1001,
+            1031,
+            1111,
+            1323,
+            2001,
+            2005,
+            2197,
+            2205,
+            3125,
+            3969,
+            4095,
+            4099,
+            5929,
+            6615,
+            14641,
+            15625,
+            16807,
+            3 * 2003,
+            7 * 1031,
+        ]
+        .iter()
+        {
+            let length = *length;
+            let mut real_planner = RealFftPlanner::<f64>::new();
+            let c2r = real_planner.plan_fft_inverse(length);
+            let mut out_a = c2r.make_output_vec();
+            let mut indata = c2r.make_input_vec();
+            let mut rustfft_check: Vec<Complex<f64>> = vec![Complex::zero(); length];
+            let mut rng = rand::thread_rng();
+            for val in indata.iter_mut() {
+                *val = Complex::new(rng.gen::<f64>(), rng.gen::<f64>());
+            }
+            indata[0].im = 0.0;
+            for (val_long, val) in rustfft_check
+                .iter_mut()
+                .take(length / 2 + 1)
+                .zip(indata.iter())
+            {
+                *val_long = *val;
+            }
+            for (val_long, val) in rustfft_check
+                .iter_mut()
+                .rev()
+                .take(length / 2)
+                .zip(indata.iter().skip(1))
+            {
+                *val_long = val.conj();
+            }
+            let mut fft_planner = FftPlanner::<f64>::new();
+            let fft = fft_planner.plan_fft_inverse(length);
+
+            c2r.process(&mut indata, &mut out_a).unwrap();
+            fft.process(&mut rustfft_check);
+
+            let check_real = rustfft_check.iter().map(|val| val.re).collect::<Vec<f64>>();
+            let maxdiff = compare_f64(&out_a, &check_real);
+            assert!(
+                maxdiff < 1.0e-9,
+                "Length: {}, too large error: {}",
+                length,
+                maxdiff
+            );
+        }
+    }
+
+    // The split and Rader algorithms need less scratch than a complex FFT of the full length
+    #[test]
+    fn odd_scratch_len() {
+        let mut planner = FftPlanner::<f64>::new();
+        for &length in [441, 1031, 7 * 1031].iter() {
+            let full_r2c =
+                RealToComplexOdd::with_radix(length, 1, Normalization::None, &mut planner);
+            let full_c2r =
+                ComplexToRealOdd::with_radix(length, 1, Normalization::None, &mut planner);
+            for algorithm in Algorithm::candidates(length)
+                .into_iter()
+                .filter(|algorithm| *algorithm != Algorithm::FullLength)
+            {
+                let r2c = build_fft_forward(length, algorithm, Normalization::None, &mut planner);
+                let c2r = build_fft_inverse(length, algorithm, Normalization::None, &mut planner);
+                assert!(r2c.get_scratch_len() < full_r2c.get_scratch_len());
+                assert!(c2r.get_scratch_len() < full_c2r.get_scratch_len());
+            }
+        }
+        assert_eq!(default_algorithm(441), Algorithm::FullLength);
+        assert_eq!(default_algorithm(1031), Algorithm::Rader);
+        assert_eq!(default_algorithm(7 * 1031), Algorithm::Split(7));
+    }
+
+    // Compare normalized transforms with scaled results of the unnormalized ones
+    #[test]
+    fn normalization() {
+        let mut real_planner = RealFftPlanner::<f64>::new();
+        let mut rng = rand::thread_rng();
+        // Include lengths that are split into subsequences or use Rader's algorithm
+        for length in (1..200).chain([1031, 2205, 3125, 6615].iter().cloned()) {
+            let r2c = real_planner.plan_fft_forward(length);
+            let c2r = real_planner.plan_fft_inverse(length);
+            let mut signal = r2c.make_input_vec();
+            for val in signal.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let mut spectrum = r2c.make_output_vec();
+            r2c.process(&mut signal.clone(), &mut spectrum).unwrap();
+            // Remove rounding errors in the values that must be real
+            spectrum[0].im = 0.0;
+            if length % 2 == 0 {
+                spectrum[length / 2].im = 0.0;
+            }
+            let mut unscaled = c2r.make_output_vec();
+            c2r.process(&mut spectrum.clone(), &mut unscaled).unwrap();
+
+            let len = length as f64;
+            for (norm, fwd_scale, inv_scale) in [
+                (Normalization::None, 1.0, 1.0),
+                (Normalization::Forward, 1.0 / len, 1.0),
+                (Normalization::Backward, 1.0, 1.0 / len),
+                (Normalization::Ortho, 1.0 / len.sqrt(), 1.0 / len.sqrt()),
+            ]
+            .iter()
+            {
+                let r2c_norm = real_planner.plan_fft_forward_normalized(length, *norm);
+                let mut spectrum_norm = r2c_norm.make_output_vec();
+                r2c_norm
+                    .process(&mut signal.clone(), &mut spectrum_norm)
+                    .unwrap();
+                let expected: Vec<Complex<f64>> =
+                    spectrum.iter().map(|val| val * fwd_scale).collect();
+                let maxdiff = compare_complex(&spectrum_norm, &expected);
+                assert!(
+                    maxdiff < 1.0e-9,
+                    "Length: {}, {:?}, too large error: {}",
+                    length,
+                    norm,
+                    maxdiff
+                );
+
+                let c2r_norm = real_planner.plan_fft_inverse_normalized(length, *norm);
+                let mut output = c2r_norm.make_output_vec();
+                c2r_norm
+                    .process(&mut spectrum.clone(), &mut output)
+                    .unwrap();
+                let expected: Vec<f64> = unscaled.iter().map(|val| val * inv_scale).collect();
+                let maxdiff = compare_f64(&output, &expected);
+                assert!(
+                    maxdiff < 1.0e-9,
+                    "Length: {}, {:?}, too large error: {}",
+                    length,
+                    norm,
+                    maxdiff
+                );
+
+                // Forward followed by inverse gives back the signal for all but the unnormalized transforms
+                if *norm != Normalization::None {
+                    spectrum_norm[0].im = 0.0;
+                    if length % 2 == 0 {
+                        spectrum_norm[length / 2].im = 0.0;
+                    }
+                    c2r_norm.process(&mut spectrum_norm, &mut output).unwrap();
+                    let maxdiff = compare_f64(&output, &signal);
+                    assert!(
+                        maxdiff < 1.0e-9,
+                        "Length: {}, {:?}, too large error: {}",
+                        length,
+                        norm,
+                        maxdiff
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fast_lengths() {
+        assert_eq!(next_fast_len(0), 2);
+        assert_eq!(next_fast_len(1), 2);
+        assert_eq!(next_fast_len(7), 8);
+        assert_eq!(next_fast_len(13), 16);
+        assert_eq!(next_fast_len(29), 30);
+        assert_eq!(next_fast_len(1001), 1024);
+        assert_eq!(next_fast_len(1081), 1152);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fast_length_overflow() {
+        next_fast_len(usize::MAX - 1);
+    }
+
+    // Check eviction, removal and inspection of the cached FFTs
+    #[test]
+    fn planner_cache() {
+        let mut real_planner = RealFftPlanner::<f64>::new();
+        let first = real_planner.plan_fft_forward(64);
+        for len in [32, 48, 100].iter() {
+            real_planner.plan_fft_forward(*len);
+            real_planner.plan_fft_inverse(*len);
+        }
+        assert_eq!(real_planner.cached_lengths(), vec![32, 48, 64, 100]);
+        assert_eq!(
+            real_planner.cache_footprint(),
+            (2 * 32 + 2 * 48 + 64 + 2 * 100) * std::mem::size_of::<Complex<f64>>()
+        );
+        // A DHT counts its forward FFT and its spectrum
+        real_planner.plan_dht(64);
+        assert_eq!(
+            real_planner.cache_footprint(),
+            (2 * 32 + 2 * 48 + 2 * 64 + 33 + 2 * 100) * std::mem::size_of::<Complex<f64>>()
+        );
+
+        // Using an FFT makes it the most recently used one
+        real_planner.plan_fft_forward(64);
+        real_planner.set_cache_capacity(Some(2));
+        assert_eq!(real_planner.cached_lengths(), vec![48, 64, 100]);
+        real_planner.plan_fft_forward(20);
+        assert_eq!(real_planner.cached_lengths(), vec![20, 48, 64, 100]);
+
+        real_planner.remove(48);
+        assert_eq!(real_planner.cached_lengths(), vec![20, 64, 100]);
+        real_planner.clear();
+        assert!(real_planner.cached_lengths().is_empty());
+        assert_eq!(real_planner.cache_footprint(), 0);
+
+        // Evicted FFTs still work
+        let mut input = first.make_input_vec();
+        let mut output = first.make_output_vec();
+        assert!(first.process(&mut input, &mut output).is_ok());
+
+        // The inner FFTs are kept when evicting, and are shared when planning again
+        real_planner.set_cache_capacity(Some(1));
+        real_planner.plan_fft_forward(64);
+        let inner = real_planner.complex_planner().plan_fft_forward(32);
+        real_planner.plan_fft_forward(100);
+        assert_eq!(real_planner.cached_lengths(), vec![100]);
+        real_planner.plan_fft_forward(64);
+        let replanned = real_planner.complex_planner().plan_fft_forward(32);
+        assert_eq!(
+            &*inner as *const dyn Fft<f64> as *const u8,
+            &*replanned as *const dyn Fft<f64> as *const u8
+        );
+    }
+
     // Test that ComplexToReal returns the right errors
     #[test]
     fn complex_to_real_errors_even() {
@@ -1108,6 +2652,168 @@ mod tests {
         }
     }
 
+    // Compare RealToComplex with standard FFT for odd lengths that are split into subsequences,
+    // and for prime lengths that use Rader's algorithm
+    #[test]
+    fn real_to_complex_odd_split() {
+        for length in [
+            37,
+            47,
+            169,
+            221,
+            1001,
+            1031,
+            1111,
+            1323,
+            2001,
+            2005,
+            2197,
+            2205,
+            3125,
+            3969,
+            4095,
+            4099,
+            5929,
+            6615,
+            14641,
+            15625,
+            16807,
+            3 * 2003,
+            7 * 1031,
+        ]
+        .iter()
+        {
+            let length = *length;
+            let mut real_planner = RealFftPlanner::<f64>::new();
+            let r2c = real_planner.plan_fft_forward(length);
+            let mut out_a = r2c.make_output_vec();
+            let mut indata = r2c.make_input_vec();
+            let mut rng = rand::thread_rng();
+            for val in indata.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let mut rustfft_check = indata
+                .iter()
+                .map(Complex::from)
+                .collect::<Vec<Complex<f64>>>();
+            let mut fft_planner = FftPlanner::<f64>::new();
+            let fft = fft_planner.plan_fft_forward(length);
+
+            fft.process(&mut rustfft_check);
+            r2c.process(&mut indata, &mut out_a).unwrap();
+            let maxdiff = compare_complex(&out_a, &rustfft_check[0..(length / 2 + 1)]);
+            assert!(
+                maxdiff < 1.0e-9,
+                "Length: {}, too large error: {}",
+                length,
+                maxdiff
+            );
+        }
+    }
+
+    // Compare batch processing with transforming one signal at a time
+    #[test]
+    fn real_to_complex_batch() {
+        for length in [1, 2, 5, 64, 99].iter() {
+            let length = *length;
+            let count = 7;
+            let mut real_planner = RealFftPlanner::<f64>::new();
+            let r2c = real_planner.plan_fft_forward(length);
+            let mut rng = rand::thread_rng();
+            let indata = (0..count * length)
+                .map(|_| rng.gen::<f64>())
+                .collect::<Vec<f64>>();
+            let mut out_batch = vec![Complex::zero(); count * (length / 2 + 1)];
+            r2c.process_batch(&mut indata.clone(), &mut out_batch)
+                .unwrap();
+            for (signal, spectrum) in indata.chunks(length).zip(out_batch.chunks(length / 2 + 1)) {
+                let mut out_single = r2c.make_output_vec();
+                r2c.process(&mut signal.to_vec(), &mut out_single).unwrap();
+                let maxdiff = compare_complex(spectrum, &out_single);
+                assert!(
+                    maxdiff < 1.0e-12,
+                    "Length: {}, too large error: {}",
+                    length,
+                    maxdiff
+                );
+            }
+            let res = r2c.process_batch(&mut indata.clone(), &mut out_batch[1..]);
+            assert!(matches!(res, Err(FftError::OutputBuffer(_, _))));
+        }
+    }
+
+    // Compare batch processing with transforming one spectrum at a time, and check that invalid values are reported
+    #[test]
+    fn complex_to_real_batch() {
+        for length in [1, 2, 5, 64, 99].iter() {
+            let length = *length;
+            let count = 7;
+            let mut real_planner = RealFftPlanner::<f64>::new();
+            let c2r = real_planner.plan_fft_inverse(length);
+            let mut rng = rand::thread_rng();
+            let mut indata = (0..count * (length / 2 + 1))
+                .map(|_| Complex::new(rng.gen::<f64>(), rng.gen::<f64>()))
+                .collect::<Vec<Complex<f64>>>();
+            for spectrum in indata.chunks_mut(length / 2 + 1) {
+                spectrum[0].im = 0.0;
+                if length % 2 == 0 {
+                    spectrum[length / 2].im = 0.0;
+                }
+            }
+            let mut out_batch = vec![0.0; count * length];
+            c2r.process_batch(&mut indata.clone(), &mut out_batch)
+                .unwrap();
+            for (spectrum, signal) in indata.chunks(length / 2 + 1).zip(out_batch.chunks(length)) {
+                let mut out_single = c2r.make_output_vec();
+                c2r.process(&mut spectrum.to_vec(), &mut out_single)
+                    .unwrap();
+                let maxdiff = compare_f64(signal, &out_single);
+                assert!(
+                    maxdiff < 1.0e-12,
+                    "Length: {}, too large error: {}",
+                    length,
+                    maxdiff
+                );
+            }
+            indata[length / 2 + 1].im = 1.0;
+            let res = c2r.process_batch(&mut indata, &mut out_batch);
+            assert!(matches!(res, Err(FftError::InputValues(true, _))));
+        }
+    }
+
+    // Compare RealToComplexPair with transforming each signal separately
+    #[test]
+    fn real_to_complex_pair() {
+        for length in 1..100 {
+            let mut real_planner = RealFftPlanner::<f64>::new();
+            let pair = real_planner.plan_fft_forward_pair(length);
+            let r2c = real_planner.plan_fft_forward(length);
+            let mut rng = rand::thread_rng();
+            let mut indata_a = pair.make_input_vec();
+            let mut indata_b = pair.make_input_vec();
+            for (val_a, val_b) in indata_a.iter_mut().zip(indata_b.iter_mut()) {
+                *val_a = rng.gen::<f64>();
+                *val_b = rng.gen::<f64>();
+            }
+            let mut out_a = pair.make_output_vec();
+            let mut out_b = pair.make_output_vec();
+            pair.process(&indata_a, &indata_b, &mut out_a, &mut out_b)
+                .unwrap();
+
+            let mut check_a = r2c.make_output_vec();
+            let mut check_b = r2c.make_output_vec();
+            r2c.process(&mut indata_a, &mut check_a).unwrap();
+            r2c.process(&mut indata_b, &mut check_b).unwrap();
+            let maxdiff = compare_complex(&out_a, &check_a).max(compare_complex(&out_b, &check_b));
+            assert!(
+                maxdiff < 1.0e-9,
+                "Length: {}, too large error: {}",
+                length,
+                maxdiff
+            );
+        }
+    }
+
     // Check that the ? operator works on the custom errors. No need to run, just needs to compile.
     #[allow(dead_code)]
     fn test_error() -> Result<(), Box<dyn Error>> {
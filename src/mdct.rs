@@ -0,0 +1,415 @@
+use crate::{as_real, Dct4, FftError, RealFftPlanner, RealToReal, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+
+/// An MDCT that takes a frame of 2*N real-valued samples and transforms it to N coefficients,
+/// defined as `X[k] = sum(w[n] * x[n] * cos(pi * (n + 1/2 + N/2) * (k + 1/2) / N))` for `n` in `0..2*N`.
+///
+/// The windowed frame is folded into N values, which are then transformed using a DCT-IV.
+/// Frames are expected to overlap by N samples. See `Imdct` for the inverse transform.
+pub struct Mdct<T> {
+    length: usize,
+    dct: Dct4<T>,
+    window: Vec<T>,
+    scratch_len: usize,
+}
+
+/// An IMDCT that takes N coefficients and transforms them to a frame of 2*N real-valued samples,
+/// defined as `y[n] = w[n] * sum(X[k] * cos(pi * (n + 1/2 + N/2) * (k + 1/2) / N))` for `k` in `0..N`.
+///
+/// The coefficients are transformed using a DCT-IV, and the result is unfolded into the windowed output frame.
+///
+/// The output frames must be overlap-added to cancel the time-domain aliasing of the MDCT.
+/// When the analysis and synthesis windows fulfil the Princen-Bradley condition, `w[n]^2 + w[n+N]^2 = 1`,
+/// the overlap-added result is the original signal multiplied by `N/2`.
+/// Like the FFTs, the transforms are not normalized, and the result must be scaled by `2/N` to recover the input.
+pub struct Imdct<T> {
+    length: usize,
+    dct: Dct4<T>,
+    window: Vec<T>,
+    scratch_len: usize,
+}
+
+/// Make a sine window of the given length, `w[n] = sin(pi * (n + 1/2) / len)`.
+/// This fulfils the Princen-Bradley condition, when used with an MDCT of `len/2` coefficients.
+pub fn sine_window<T: FftNum>(len: usize) -> Vec<T> {
+    (0..len)
+        .map(|n| {
+            let angle = std::f64::consts::PI * (n as f64 + 0.5) / len as f64;
+            T::from_f64(angle.sin()).unwrap()
+        })
+        .collect()
+}
+
+/// Make the window used by Vorbis, `w[n] = sin(pi/2 * sin^2(pi * (n + 1/2) / len))`.
+/// This fulfils the Princen-Bradley condition, when used with an MDCT of `len/2` coefficients.
+pub fn vorbis_window<T: FftNum>(len: usize) -> Vec<T> {
+    (0..len)
+        .map(|n| {
+            let inner = (std::f64::consts::PI * (n as f64 + 0.5) / len as f64).sin();
+            let angle = std::f64::consts::FRAC_PI_2 * inner * inner;
+            T::from_f64(angle.sin()).unwrap()
+        })
+        .collect()
+}
+
+fn check_parameters<T>(length: usize, window: &[T]) {
+    if length % 2 > 0 {
+        panic!("MDCT length must be even, got {}", length);
+    }
+    if window.len() != 2 * length {
+        panic!("Window length must be {}, got {}", 2 * length, window.len());
+    }
+}
+
+impl<T: FftNum> Mdct<T> {
+    /// Create a new MDCT giving `length` coefficients from frames of 2*`length` samples, using the given window.
+    /// Uses the given RealFftPlanner to build the inner FFT.
+    /// Panics if the length is odd, or if the window is not 2*`length` long.
+    pub fn new(length: usize, window: Vec<T>, planner: &mut RealFftPlanner<T>) -> Self {
+        check_parameters(length, &window);
+        let dct = Dct4::new(length, planner);
+        let scratch_len = length / 2 + dct.get_scratch_len();
+        Mdct {
+            length,
+            dct,
+            window,
+            scratch_len,
+        }
+    }
+
+    /// Transform a frame of 2*N real-valued samples, storing the N coefficients in the output vector.
+    /// The input buffer is not modified.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process(&self, input: &[T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a frame of 2*N real-valued samples, storing the N coefficients in the output vector.
+    /// The input buffer is not modified.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &[T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != 2 * self.length {
+            return Err(FftError::InputBuffer(2 * self.length, input.len()));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let (folded, dct_scratch) = scratch.split_at_mut(self.length / 2);
+        let folded = as_real(folded);
+        let half = self.length / 2;
+        let w = &self.window;
+        let x = input;
+        // Fold the four quarters (a, b, c, d) of the windowed frame into (-c_rev - d, a - b_rev)
+        for n in 0..half {
+            folded[n] =
+                -w[3 * half - 1 - n] * x[3 * half - 1 - n] - w[3 * half + n] * x[3 * half + n];
+            folded[half + n] = w[n] * x[n] - w[self.length - 1 - n] * x[self.length - 1 - n];
+        }
+        self.dct.process_with_scratch(folded, output, dct_scratch)
+    }
+
+    /// Get the window that is applied to the input frames.
+    pub fn window(&self) -> &[T] {
+        &self.window
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Get the number of coefficients produced by this MDCT. The frames are twice this long.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); 2 * self.length]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.length]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> Imdct<T> {
+    /// Create a new IMDCT giving frames of 2*`length` samples from `length` coefficients, using the given window.
+    /// Uses the given RealFftPlanner to build the inner FFT.
+    /// Panics if the length is odd, or if the window is not 2*`length` long.
+    pub fn new(length: usize, window: Vec<T>, planner: &mut RealFftPlanner<T>) -> Self {
+        check_parameters(length, &window);
+        let dct = Dct4::new(length, planner);
+        let scratch_len = length / 2 + dct.get_scratch_len();
+        Imdct {
+            length,
+            dct,
+            window,
+            scratch_len,
+        }
+    }
+
+    // Transform the coefficients with the DCT-IV, and call `store` with the index and value of each unwindowed output sample.
+    fn process_inner<F: FnMut(usize, T)>(
+        &self,
+        input: &mut [T],
+        scratch: &mut [Complex<T>],
+        mut store: F,
+    ) -> Res<()> {
+        let (unfolded, dct_scratch) = scratch.split_at_mut(self.length / 2);
+        let unfolded = as_real(unfolded);
+        self.dct
+            .process_with_scratch(input, unfolded, dct_scratch)?;
+        let half = self.length / 2;
+        let u = unfolded;
+        // Unfold the two halves (u1, u2) into (u2, -u2_rev, -u1_rev, -u1)
+        for n in 0..half {
+            store(n, u[half + n]);
+            store(half + n, -u[self.length - 1 - n]);
+            store(self.length + n, -u[half - 1 - n]);
+            store(3 * half + n, -u[n]);
+        }
+        Ok(())
+    }
+
+    /// Transform N coefficients, storing the windowed frame of 2*N samples in the output vector.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform N coefficients, storing the windowed frame of 2*N samples in the output vector.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        if output.len() != 2 * self.length {
+            return Err(FftError::OutputBuffer(2 * self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let window = &self.window;
+        self.process_inner(input, scratch, |idx, value| {
+            output[idx] = window[idx] * value;
+        })
+    }
+
+    /// Transform N coefficients and overlap-add the windowed frame of 2*N samples.
+    /// The first half of the frame is added to the `overlap` from the previous frame and stored in the N long output.
+    /// The second half of the frame is then stored in `overlap`, to be added to the next frame.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// A wrong length of `overlap` is reported as an `FftError::OutputBuffer` error.
+    pub fn process_overlap_add(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        overlap: &mut [T],
+    ) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_overlap_add_with_scratch(input, output, overlap, &mut scratch)
+    }
+
+    /// Transform N coefficients and overlap-add the windowed frame of 2*N samples.
+    /// The first half of the frame is added to the `overlap` from the previous frame and stored in the N long output.
+    /// The second half of the frame is then stored in `overlap`, to be added to the next frame.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// A wrong length of `overlap` is reported as an `FftError::OutputBuffer` error.
+    pub fn process_overlap_add_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        overlap: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if overlap.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, overlap.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let window = &self.window;
+        let length = self.length;
+        // The first half of the frame only needs the old overlap, which is then replaced by the second half
+        self.process_inner(input, scratch, |idx, value| {
+            if idx < length {
+                output[idx] = overlap[idx] + window[idx] * value;
+            } else {
+                overlap[idx - length] = window[idx] * value;
+            }
+        })
+    }
+
+    /// Get the window that is applied to the output frames.
+    pub fn window(&self) -> &[T] {
+        &self.window
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Get the number of coefficients used by this IMDCT. The frames are twice this long.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.length]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); 2 * self.length]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{sine_window, vorbis_window, Imdct, Mdct, RealFftPlanner};
+    use rand::Rng;
+
+    fn kernel(n: usize, k: usize, length: usize) -> f64 {
+        let n = n as f64 + 0.5 + length as f64 / 2.0;
+        (std::f64::consts::PI * n * (k as f64 + 0.5) / length as f64).cos()
+    }
+
+    // Compare MDCT and IMDCT with the direct sums
+    #[test]
+    fn mdct_imdct() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        for &length in [2, 4, 6, 8, 16, 30, 64].iter() {
+            let window = sine_window::<f64>(2 * length);
+            let mdct = Mdct::new(length, window.clone(), &mut planner);
+            let imdct = Imdct::new(length, window.clone(), &mut planner);
+            let mut rng = rand::thread_rng();
+            let mut frame = mdct.make_input_vec();
+            for val in frame.iter_mut() {
+                *val = rng.gen::<f64>();
+            }
+            let mut coeffs = mdct.make_output_vec();
+            mdct.process(&frame, &mut coeffs).unwrap();
+            for (k, coeff) in coeffs.iter().enumerate() {
+                let expected = (0..2 * length)
+                    .map(|n| window[n] * frame[n] * kernel(n, k, length))
+                    .sum::<f64>();
+                let diff = (expected - coeff).abs();
+                assert!(
+                    diff < 1.0e-9,
+                    "Length: {}, too large error: {}",
+                    length,
+                    diff
+                );
+            }
+
+            let expected = (0..2 * length)
+                .map(|n| {
+                    window[n]
+                        * coeffs
+                            .iter()
+                            .enumerate()
+                            .map(|(k, x)| x * kernel(n, k, length))
+                            .sum::<f64>()
+                })
+                .collect::<Vec<f64>>();
+            let mut output = imdct.make_output_vec();
+            imdct.process(&mut coeffs, &mut output).unwrap();
+            for (e, o) in expected.iter().zip(output.iter()) {
+                let diff = (e - o).abs();
+                assert!(
+                    diff < 1.0e-9,
+                    "Length: {}, too large error: {}",
+                    length,
+                    diff
+                );
+            }
+        }
+    }
+
+    // Check that overlap-adding the frames gives back the original signal, scaled by half the length
+    #[test]
+    fn mdct_overlap_add() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let length = 32;
+        let frames = 8;
+        let window = vorbis_window::<f64>(2 * length);
+        let mdct = Mdct::new(length, window.clone(), &mut planner);
+        let imdct = Imdct::new(length, window, &mut planner);
+        let mut rng = rand::thread_rng();
+        let signal = (0..(frames + 1) * length)
+            .map(|_| rng.gen::<f64>())
+            .collect::<Vec<f64>>();
+        let mut coeffs = mdct.make_output_vec();
+        let mut output = vec![0.0; length];
+        let mut overlap = vec![0.0; length];
+        let mut scratch = imdct.make_scratch_vec();
+        for frame in 0..frames {
+            mdct.process(&signal[frame * length..(frame + 2) * length], &mut coeffs)
+                .unwrap();
+            imdct
+                .process_overlap_add_with_scratch(
+                    &mut coeffs,
+                    &mut output,
+                    &mut overlap,
+                    &mut scratch,
+                )
+                .unwrap();
+            // The first half of the first frame has no previous frame to cancel the aliasing
+            if frame > 0 {
+                for (orig, val) in signal[frame * length..].iter().zip(output.iter()) {
+                    let diff = (orig - 2.0 * val / length as f64).abs();
+                    assert!(diff < 1.0e-9, "Frame: {}, too large error: {}", frame, diff);
+                }
+            }
+        }
+    }
+}
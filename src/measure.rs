@@ -0,0 +1,166 @@
+use crate::wisdom::{Algorithm, Choice};
+use crate::{
+    build_chosen_forward, build_chosen_inverse, ComplexToReal, Normalization, Planners,
+    RealToComplex,
+};
+use rustfft::FftNum;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// The number of timed repetitions for each candidate. The fastest repetition is used.
+const REPETITIONS: usize = 3;
+
+// The minimum number of values transformed in each repetition, to get measurable times for short lengths.
+const MIN_VALUES_PER_REPETITION: usize = 1 << 16;
+
+// Measure the time of running a transform of the given length, after running it once to warm up.
+fn time_runs<F: FnMut()>(len: usize, mut run: F) -> Duration {
+    let iterations = (MIN_VALUES_PER_REPETITION / len.max(1)).max(1);
+    run();
+    (0..REPETITIONS)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                run();
+            }
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+// Get the choices that are measured for the length.
+// The scalar planner of RustFFT is only a separate candidate on the architectures where the default planner
+// uses SIMD instructions, since the default planner falls back to the scalar one elsewhere.
+fn candidates(len: usize) -> Vec<Choice> {
+    let scalar_options: &[bool] = if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        &[false, true]
+    } else {
+        &[false]
+    };
+    Algorithm::candidates(len)
+        .into_iter()
+        .flat_map(|algorithm| {
+            scalar_options
+                .iter()
+                .map(move |&scalar| Choice { algorithm, scalar })
+        })
+        .collect()
+}
+
+// Build the forward FFTs of all candidate choices for the length, and return the fastest one.
+// Nothing is timed if there is only one candidate.
+// The input is all zeros, which stays zero when the transform uses it as scratch space.
+pub(crate) fn fastest_forward<T: FftNum>(
+    len: usize,
+    normalization: Normalization,
+    planners: Planners<'_, T>,
+) -> (Choice, Arc<dyn RealToComplex<T>>) {
+    let (planner, scalar_planner) = planners;
+    let candidates = candidates(len);
+    if candidates.len() == 1 {
+        let choice = candidates[0];
+        let fft = build_chosen_forward(len, choice, normalization, (planner, scalar_planner));
+        return (choice, fft);
+    }
+    let mut fastest: Option<(Duration, Choice, Arc<dyn RealToComplex<T>>)> = None;
+    for choice in candidates.into_iter() {
+        let fft = build_chosen_forward(
+            len,
+            choice,
+            normalization,
+            (&mut *planner, &mut *scalar_planner),
+        );
+        let mut input = fft.make_input_vec();
+        let mut output = fft.make_output_vec();
+        let mut scratch = fft.make_scratch_vec();
+        let time = time_runs(len, || {
+            let _ = fft.process_with_scratch(&mut input, &mut output, &mut scratch);
+        });
+        if fastest.as_ref().map_or(true, |(best, _, _)| time < *best) {
+            fastest = Some((time, choice, fft));
+        }
+    }
+    let (_, choice, fft) = fastest.unwrap();
+    (choice, fft)
+}
+
+// Build the inverse FFTs of all candidate choices for the length, and return the fastest one.
+// Nothing is timed if there is only one candidate.
+// The input is all zeros, which stays zero when the transform uses it as scratch space.
+pub(crate) fn fastest_inverse<T: FftNum>(
+    len: usize,
+    normalization: Normalization,
+    planners: Planners<'_, T>,
+) -> (Choice, Arc<dyn ComplexToReal<T>>) {
+    let (planner, scalar_planner) = planners;
+    let candidates = candidates(len);
+    if candidates.len() == 1 {
+        let choice = candidates[0];
+        let fft = build_chosen_inverse(len, choice, normalization, (planner, scalar_planner));
+        return (choice, fft);
+    }
+    let mut fastest: Option<(Duration, Choice, Arc<dyn ComplexToReal<T>>)> = None;
+    for choice in candidates.into_iter() {
+        let fft = build_chosen_inverse(
+            len,
+            choice,
+            normalization,
+            (&mut *planner, &mut *scalar_planner),
+        );
+        let mut input = fft.make_input_vec();
+        let mut output = fft.make_output_vec();
+        let mut scratch = fft.make_scratch_vec();
+        let time = time_runs(len, || {
+            let _ = fft.process_with_scratch(&mut input, &mut output, &mut scratch);
+        });
+        if fastest.as_ref().map_or(true, |(best, _, _)| time < *best) {
+            fastest = Some((time, choice, fft));
+        }
+    }
+    let (_, choice, fft) = fastest.unwrap();
+    (choice, fft)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Normalization, PlanningMode, RealFftPlanner};
+    use rand::Rng;
+
+    // Compare FFTs planned by measuring with the ones planned from the length alone
+    #[test]
+    fn measured_planning() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut measured = RealFftPlanner::<f64>::new();
+        measured.set_planning_mode(PlanningMode::Measure);
+        let mut rng = rand::thread_rng();
+        for &len in [1, 16, 45, 75, 77].iter() {
+            let fft = measured.plan_fft_forward_normalized(len, Normalization::Ortho);
+            let reference = planner.plan_fft_forward_normalized(len, Normalization::Ortho);
+            let input: Vec<f64> = (0..len).map(|_| rng.gen::<f64>()).collect();
+            let mut output = fft.make_output_vec();
+            let mut expected = fft.make_output_vec();
+            fft.process(&mut input.clone(), &mut output).unwrap();
+            reference
+                .process(&mut input.clone(), &mut expected)
+                .unwrap();
+            for (val, exp) in output.iter().zip(expected.iter()) {
+                assert!((val - exp).norm() < 1.0e-9);
+            }
+
+            let ifft = measured.plan_fft_inverse(len);
+            let mut signal = ifft.make_output_vec();
+            ifft.process(&mut output, &mut signal).unwrap();
+            for (val, x) in signal.iter().zip(input.iter()) {
+                assert!((val / (len as f64).sqrt() - x).abs() < 1.0e-9);
+            }
+        }
+
+        // The measured choices are exported as wisdom
+        let wisdom = measured.export_wisdom();
+        assert_eq!(wisdom.lines().count(), 11);
+        assert!(wisdom.contains("forward 1 full"));
+        let mut other = RealFftPlanner::<f64>::new();
+        assert!(other.import_wisdom(&wisdom).is_ok());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::fir::{BlockBuffer, BlockProcessor};
+use crate::{ComplexToReal, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::sync::Arc;
+
+/// A streaming convolver for long impulse responses, using uniformly partitioned convolution.
+///
+/// The impulse response is split into partitions of `block_len` samples, and the spectrum of each partition
+/// is calculated once, using real FFTs of length 2*`block_len`.
+/// The input is collected into blocks of `block_len` samples. The spectrum of each block is stored in a
+/// frequency-domain delay line, and the output block is the overlap-save result of the sum of the products of the
+/// delayed spectra with the partitions.
+/// This means that the output is delayed by `block_len` samples, independently of the length of the impulse response.
+/// The input may be given in chunks of any size, and the same number of output samples is returned for each chunk.
+///
+/// All buffers are allocated when the convolver is created, so processing does not allocate.
+pub struct PartitionedConvolver<T: FftNum> {
+    block_len: usize,
+    r2c: Arc<dyn RealToComplex<T>>,
+    c2r: Arc<dyn ComplexToReal<T>>,
+    // The spectra of the partitions, one after the other
+    partitions: Vec<Complex<T>>,
+    // The spectra of the latest input blocks, used as a ring buffer
+    delay_line: Vec<Complex<T>>,
+    newest: usize,
+    blocks: BlockBuffer<T>,
+    previous_block: Vec<T>,
+    buffer: Vec<T>,
+    accumulator: Vec<Complex<T>>,
+    scratch: Vec<Complex<T>>,
+}
+
+impl<T: FftNum> PartitionedConvolver<T> {
+    /// Create a new convolver for the given impulse response, that processes the input in blocks of `block_len` samples.
+    /// Uses the given RealFftPlanner to build the FFTs.
+    /// Panics if the impulse response is empty or if `block_len` is zero.
+    pub fn new(impulse_response: &[T], block_len: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        if impulse_response.is_empty() {
+            panic!("The impulse response must not be empty");
+        }
+        if block_len == 0 {
+            panic!("The block length must be at least 1");
+        }
+        let r2c = planner.plan_fft_forward(2 * block_len);
+        let c2r = planner.plan_fft_inverse(2 * block_len);
+        let bins = block_len + 1;
+        let mut scratch = vec![Complex::zero(); r2c.get_scratch_len().max(c2r.get_scratch_len())];
+        let mut buffer = r2c.make_input_vec();
+
+        // Include the scaling of the iFFT in the partitions
+        let scale = T::one() / T::from_usize(2 * block_len).unwrap();
+        let count = (impulse_response.len() + block_len - 1) / block_len;
+        let mut partitions = vec![Complex::zero(); count * bins];
+        for (partition, spectrum) in impulse_response
+            .chunks(block_len)
+            .zip(partitions.chunks_exact_mut(bins))
+        {
+            for val in buffer.iter_mut() {
+                *val = T::zero();
+            }
+            for (val, sample) in buffer.iter_mut().zip(partition.iter()) {
+                *val = *sample * scale;
+            }
+            r2c.process_with_scratch(&mut buffer, spectrum, &mut scratch)
+                .unwrap();
+        }
+
+        PartitionedConvolver {
+            block_len,
+            delay_line: vec![Complex::zero(); count * bins],
+            newest: 0,
+            partitions,
+            blocks: BlockBuffer::new(block_len),
+            previous_block: vec![T::zero(); block_len],
+            buffer,
+            accumulator: vec![Complex::zero(); bins],
+            scratch,
+            r2c,
+            c2r,
+        }
+    }
+
+    /// Convolve a chunk of input samples, storing the same number of output samples in the output.
+    /// The output is delayed by `block_len` samples.
+    /// An error is returned if the output does not have the same length as the input.
+    pub fn process(&mut self, input: &[T], output: &mut [T]) -> Res<()> {
+        self.process_chunk(input, output)
+    }
+
+    /// Clear the internal state, as if no input had been processed.
+    pub fn reset(&mut self) {
+        self.blocks.reset();
+        for val in self.previous_block.iter_mut() {
+            *val = T::zero();
+        }
+        for val in self.delay_line.iter_mut() {
+            *val = Complex::zero();
+        }
+    }
+
+    /// Get the number of samples that the output is delayed, which equals the block length.
+    pub fn latency(&self) -> usize {
+        self.block_len
+    }
+
+    /// Get the length of the blocks that the input is processed in.
+    pub fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// Get the number of partitions that the impulse response was split into.
+    pub fn partitions(&self) -> usize {
+        self.partitions.len() / (self.block_len + 1)
+    }
+}
+
+impl<T: FftNum> BlockProcessor<T> for PartitionedConvolver<T> {
+    fn blocks(&mut self) -> &mut BlockBuffer<T> {
+        &mut self.blocks
+    }
+
+    fn process_block(&mut self) -> Res<()> {
+        let block_len = self.block_len;
+        let bins = block_len + 1;
+        let count = self.partitions.len() / bins;
+
+        // Transform the previous and the new input block together, and store the spectrum in the delay line
+        self.buffer[..block_len].copy_from_slice(&self.previous_block);
+        self.buffer[block_len..].copy_from_slice(&self.blocks.input);
+        self.previous_block.copy_from_slice(&self.blocks.input);
+        self.newest = (self.newest + 1) % count;
+        let newest = &mut self.delay_line[self.newest * bins..(self.newest + 1) * bins];
+        self.r2c
+            .process_with_scratch(&mut self.buffer, newest, &mut self.scratch)?;
+
+        // Multiply each partition with the spectrum delayed by the corresponding number of blocks
+        for val in self.accumulator.iter_mut() {
+            *val = Complex::zero();
+        }
+        for (idx, partition) in self.partitions.chunks_exact(bins).enumerate() {
+            let delayed = (self.newest + count - idx) % count;
+            let spectrum = &self.delay_line[delayed * bins..(delayed + 1) * bins];
+            for (acc, (value, coeff)) in self
+                .accumulator
+                .iter_mut()
+                .zip(spectrum.iter().zip(partition.iter()))
+            {
+                *acc = *acc + *value * coeff;
+            }
+        }
+
+        // The first half of the result is affected by circular wrap-around, keep only the second
+        self.c2r.process_with_scratch(
+            &mut self.accumulator,
+            &mut self.buffer,
+            &mut self.scratch,
+        )?;
+        self.blocks
+            .output
+            .copy_from_slice(&self.buffer[block_len..]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PartitionedConvolver, RealFftPlanner};
+    use rand::Rng;
+
+    // Convolve a signal in chunks of random size, and compare with a direct convolution
+    #[test]
+    fn partitioned_convolver() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut rng = rand::thread_rng();
+        for &(ir_len, block_len) in [(1, 1), (5, 16), (16, 16), (100, 7), (1000, 64)].iter() {
+            let impulse_response = (0..ir_len).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+            let signal = (0..1500).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+            let mut convolver =
+                PartitionedConvolver::new(&impulse_response, block_len, &mut planner);
+            assert_eq!(convolver.partitions(), (ir_len + block_len - 1) / block_len);
+            let mut output = vec![0.0; signal.len()];
+            let mut done = 0;
+            while done < signal.len() {
+                let count = rng.gen_range(0..100).min(signal.len() - done);
+                convolver
+                    .process(&signal[done..done + count], &mut output[done..done + count])
+                    .unwrap();
+                done += count;
+            }
+            for (n, val) in output.iter().enumerate() {
+                let expected = if n < block_len {
+                    0.0
+                } else {
+                    let time = n - block_len;
+                    impulse_response
+                        .iter()
+                        .enumerate()
+                        .filter(|(k, _)| *k <= time)
+                        .map(|(k, coeff)| coeff * signal[time - k])
+                        .sum::<f64>()
+                };
+                assert!(
+                    (expected - val).abs() < 1.0e-9,
+                    "IR: {}, block: {}, sample: {}, too large error: {}",
+                    ir_len,
+                    block_len,
+                    n,
+                    (expected - val).abs()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{as_real, FftError, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::sync::Arc;
+
+/// Selects the scaling of a power spectral estimate, with the same meaning as the `scaling` argument of `scipy.signal.welch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsdScaling {
+    /// Power spectral density, in units of power per Hz. The squared magnitudes are scaled by `1/(fs * sum(w^2))`.
+    /// Summing the result and multiplying by the bin width `fs/N` gives the mean power of the signal.
+    Density,
+    /// Power spectrum, in units of power. The squared magnitudes are scaled by `1/sum(w)^2`.
+    /// A sinusoid with amplitude `A` at the center of a bin gives a value of `A^2/2` in that bin.
+    Spectrum,
+}
+
+/// A one-sided periodogram, the squared magnitude of the FFT of a windowed segment.
+///
+/// The spectrum of a real signal is symmetric, so the power of the negative frequencies is included by doubling
+/// all bins except the first, which holds the DC component. For even lengths the last bin holds the Nyquist
+/// frequency, which has no negative counterpart, and is not doubled either.
+/// The result has `N/2+1` values, for the frequencies `k * fs / N`.
+pub struct Periodogram<T> {
+    window: Vec<T>,
+    scale: T,
+    fft: Arc<dyn RealToComplex<T>>,
+    scratch_len: usize,
+}
+
+/// Welch's method for estimating the power spectral density.
+///
+/// The signal is split into overlapping segments, and the result is the average of the periodograms of the segments.
+/// Samples after the last complete segment are not used. The segments are not detrended.
+pub struct Welch<T> {
+    periodogram: Periodogram<T>,
+    hop_len: usize,
+}
+
+impl<T: FftNum> Periodogram<T> {
+    /// Create a new periodogram for segments with the length of the given window.
+    /// The sample rate `fs` is only used for the `Density` scaling.
+    /// Uses the given RealFftPlanner to build the FFT.
+    /// Panics if the window is empty.
+    pub fn new(
+        window: Vec<T>,
+        scaling: PsdScaling,
+        sample_rate: T,
+        planner: &mut RealFftPlanner<T>,
+    ) -> Self {
+        if window.is_empty() {
+            panic!("The window must not be empty");
+        }
+        let scale = match scaling {
+            PsdScaling::Density => {
+                let sum_sq = window.iter().fold(T::zero(), |acc, w| acc + *w * *w);
+                T::one() / (sample_rate * sum_sq)
+            }
+            PsdScaling::Spectrum => {
+                let sum = window.iter().fold(T::zero(), |acc, w| acc + *w);
+                T::one() / (sum * sum)
+            }
+        };
+        let len = window.len();
+        let fft = planner.plan_fft_forward(len);
+        let scratch_len = (len + 1) / 2 + len / 2 + 1 + fft.get_scratch_len();
+        Periodogram {
+            window,
+            scale,
+            fft,
+            scratch_len,
+        }
+    }
+
+    /// Get the length of the segments.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Get the number of frequency bins of the result, `N/2+1`.
+    pub fn bins(&self) -> usize {
+        self.len() / 2 + 1
+    }
+
+    /// Get the frequencies of the bins, `k * fs / N`.
+    pub fn frequencies(&self, sample_rate: T) -> Vec<T> {
+        let len = T::from_usize(self.len()).unwrap();
+        (0..self.bins())
+            .map(|k| T::from_usize(k).unwrap() * sample_rate / len)
+            .collect()
+    }
+
+    /// Estimate the power spectrum of a segment, storing the result in the output.
+    /// It allocates scratch space as needed.
+    /// An error is returned if the input or output has the wrong length.
+    pub fn process(&self, input: &[T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Estimate the power spectrum of a segment, storing the result in the output.
+    /// It uses the provided scratch vector instead of allocating.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &[T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.len() {
+            return Err(FftError::InputBuffer(self.len(), input.len()));
+        }
+        self.check_buffers(output, scratch)?;
+        for val in output.iter_mut() {
+            *val = T::zero();
+        }
+        self.accumulate(input, output, T::one(), scratch)
+    }
+
+    /// Get the window that is applied to the segments.
+    pub fn window(&self) -> &[T] {
+        &self.window
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.bins()]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+
+    fn check_buffers(&self, output: &[T], scratch: &[Complex<T>]) -> Res<()> {
+        if output.len() != self.bins() {
+            return Err(FftError::OutputBuffer(self.bins(), output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        Ok(())
+    }
+
+    // Add the one-sided power spectrum of a segment, multiplied by `weight`, to the output.
+    fn accumulate(
+        &self,
+        input: &[T],
+        output: &mut [T],
+        weight: T,
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let len = self.len();
+        let (segment, rest) = scratch.split_at_mut((len + 1) / 2);
+        let (spectrum, fft_scratch) = rest.split_at_mut(len / 2 + 1);
+        let segment = &mut as_real(segment)[..len];
+        for ((val, x), w) in segment.iter_mut().zip(input.iter()).zip(self.window.iter()) {
+            *val = *x * *w;
+        }
+        self.fft
+            .process_with_scratch(segment, spectrum, fft_scratch)?;
+
+        // Double all bins that also represent a negative frequency.
+        let scale = self.scale * weight;
+        let doubled_scale = scale + scale;
+        let last_doubled = if len % 2 == 0 { len / 2 } else { len / 2 + 1 };
+        for (k, (out, val)) in output.iter_mut().zip(spectrum.iter()).enumerate() {
+            let factor = if k > 0 && k < last_doubled {
+                doubled_scale
+            } else {
+                scale
+            };
+            *out = *out + val.norm_sqr() * factor;
+        }
+        Ok(())
+    }
+}
+
+impl<T: FftNum> Welch<T> {
+    /// Create a new Welch estimator for segments with the length of the given window,
+    /// where consecutive segments overlap by `overlap` samples.
+    /// The sample rate `fs` is only used for the `Density` scaling.
+    /// Uses the given RealFftPlanner to build the FFT.
+    /// Panics if the window is empty, or if the overlap is not shorter than the window.
+    pub fn new(
+        window: Vec<T>,
+        overlap: usize,
+        scaling: PsdScaling,
+        sample_rate: T,
+        planner: &mut RealFftPlanner<T>,
+    ) -> Self {
+        if overlap >= window.len() {
+            panic!(
+                "Overlap must be shorter than the window length {}, got {}",
+                window.len(),
+                overlap
+            );
+        }
+        let hop_len = window.len() - overlap;
+        let periodogram = Periodogram::new(window, scaling, sample_rate, planner);
+        Welch {
+            periodogram,
+            hop_len,
+        }
+    }
+
+    /// Get the length of the segments.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.periodogram.len()
+    }
+
+    /// Get the number of frequency bins of the result, `N/2+1`.
+    pub fn bins(&self) -> usize {
+        self.periodogram.bins()
+    }
+
+    /// Get the frequencies of the bins, `k * fs / N`.
+    pub fn frequencies(&self, sample_rate: T) -> Vec<T> {
+        self.periodogram.frequencies(sample_rate)
+    }
+
+    /// Get the number of segments that are averaged for a signal of the given length.
+    pub fn segments(&self, signal_len: usize) -> usize {
+        if signal_len < self.len() {
+            return 0;
+        }
+        1 + (signal_len - self.len()) / self.hop_len
+    }
+
+    /// Estimate the power spectrum of a signal, storing the result in the output.
+    /// It allocates scratch space as needed.
+    /// An error is returned if the output has the wrong length, or if the signal is shorter than one segment.
+    pub fn process(&self, signal: &[T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(signal, output, &mut scratch)
+    }
+
+    /// Estimate the power spectrum of a signal, storing the result in the output.
+    /// It uses the provided scratch vector for all segments, instead of allocating.
+    /// An error is returned if any of the given slices has the wrong length, or if the signal is shorter than one segment.
+    pub fn process_with_scratch(
+        &self,
+        signal: &[T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let segments = self.segments(signal.len());
+        if segments == 0 {
+            return Err(FftError::InputBuffer(self.len(), signal.len()));
+        }
+        self.periodogram.check_buffers(output, scratch)?;
+        for val in output.iter_mut() {
+            *val = T::zero();
+        }
+        let weight = T::one() / T::from_usize(segments).unwrap();
+        for idx in 0..segments {
+            let start = idx * self.hop_len;
+            self.periodogram.accumulate(
+                &signal[start..start + self.len()],
+                output,
+                weight,
+                scratch,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get the window that is applied to the segments.
+    pub fn window(&self) -> &[T] {
+        self.periodogram.window()
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.periodogram.get_scratch_len()
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<T> {
+        self.periodogram.make_output_vec()
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        self.periodogram.make_scratch_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FftError, Periodogram, PsdScaling, RealFftPlanner, Welch, Window};
+    use std::f64::consts::PI;
+
+    // Check the power of sinusoids, and the DC and Nyquist bins, for even and odd lengths
+    #[test]
+    fn periodogram() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        for &len in [16, 17].iter() {
+            let window = Window::Rectangular.periodic(len);
+            let spectrum =
+                Periodogram::new(window.clone(), PsdScaling::Spectrum, 1.0, &mut planner);
+            let mut output = spectrum.make_output_vec();
+            let signal: Vec<f64> = (0..len)
+                .map(|n| 0.5 + 3.0 * (2.0 * PI * (3 * n) as f64 / len as f64).cos())
+                .collect();
+            spectrum.process(&signal, &mut output).unwrap();
+            for (k, val) in output.iter().enumerate() {
+                let expected = match k {
+                    0 => 0.25,
+                    3 => 4.5,
+                    _ => 0.0,
+                };
+                assert!((val - expected).abs() < 1.0e-9, "{} {} {}", len, k, val);
+            }
+
+            // The sum of the density times the bin width is the mean power
+            let fs = 8.0;
+            let density = Periodogram::new(window, PsdScaling::Density, fs, &mut planner);
+            let signal: Vec<f64> = (0..len).map(|n| ((n * n) % 7) as f64 - 2.5).collect();
+            density.process(&signal, &mut output).unwrap();
+            let power = output.iter().sum::<f64>() * fs / len as f64;
+            let mean_sq = signal.iter().map(|x| x * x).sum::<f64>() / len as f64;
+            assert!((power - mean_sq).abs() < 1.0e-9, "{} {}", power, mean_sq);
+        }
+
+        // The Nyquist bin of an even length is not doubled
+        let spectrum = Periodogram::new(
+            Window::Rectangular.periodic(8),
+            PsdScaling::Spectrum,
+            1.0,
+            &mut planner,
+        );
+        let mut output = spectrum.make_output_vec();
+        let signal: Vec<f64> = (0..8)
+            .map(|n| if n % 2 == 0 { 2.0 } else { -2.0 })
+            .collect();
+        spectrum.process(&signal, &mut output).unwrap();
+        assert!((output[4] - 4.0).abs() < 1.0e-9);
+        assert_eq!(spectrum.frequencies(8.0), vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+
+    // Compare Welch with the average of the periodograms of the segments
+    #[test]
+    fn welch() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let len = 12;
+        let window = Window::Hann.periodic(len);
+        let periodogram = Periodogram::new(window.clone(), PsdScaling::Density, 2.0, &mut planner);
+        let welch = Welch::new(window, 4, PsdScaling::Density, 2.0, &mut planner);
+        let signal: Vec<f64> = (0..45)
+            .map(|n| (0.3 * n as f64).sin() + (n % 5) as f64)
+            .collect();
+        assert_eq!(welch.segments(signal.len()), 5);
+
+        let mut expected = periodogram.make_output_vec();
+        let mut segment_psd = periodogram.make_output_vec();
+        for idx in 0..5 {
+            periodogram
+                .process(&signal[8 * idx..8 * idx + len], &mut segment_psd)
+                .unwrap();
+            for (exp, val) in expected.iter_mut().zip(segment_psd.iter()) {
+                *exp += val / 5.0;
+            }
+        }
+        let mut output = welch.make_output_vec();
+        welch.process(&signal, &mut output).unwrap();
+        for (val, exp) in output.iter().zip(expected.iter()) {
+            assert!((val - exp).abs() < 1.0e-12);
+        }
+
+        let res = welch.process(&signal[..11], &mut output);
+        assert!(matches!(res, Err(FftError::InputBuffer(12, 11))));
+        let res = welch.process(&signal, &mut output[..6]);
+        assert!(matches!(res, Err(FftError::OutputBuffer(7, 6))));
+    }
+}
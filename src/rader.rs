@@ -0,0 +1,443 @@
+use crate::{
+    as_real, has_only_factors, next_fast_len, ComplexPlanner, ComplexToReal, ComplexToRealEven,
+    FftError, Normalization, RealToComplex, RealToComplexEven, Res,
+};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::iter;
+
+// Check if a number is a prime, by trial division.
+pub(crate) fn is_prime(value: usize) -> bool {
+    if value < 2 {
+        return false;
+    }
+    let mut factor = 2;
+    while factor <= value / factor {
+        if value % factor == 0 {
+            return false;
+        }
+        factor += 1;
+    }
+    true
+}
+
+// Calculate `first * second mod modulus`, without overflowing.
+fn mul_mod(first: usize, second: usize, modulus: usize) -> usize {
+    (first as u128 * second as u128 % modulus as u128) as usize
+}
+
+// Calculate `base^exponent mod modulus`.
+fn pow_mod(base: usize, mut exponent: usize, modulus: usize) -> usize {
+    let mut base = base % modulus;
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent % 2 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent /= 2;
+    }
+    result
+}
+
+// Find the smallest primitive root modulo a prime, meaning that its powers give all the values from 1 to `prime-1`.
+fn primitive_root(prime: usize) -> usize {
+    let mut factors = Vec::new();
+    let mut rest = prime - 1;
+    let mut factor = 2;
+    while factor <= rest / factor {
+        if rest % factor == 0 {
+            factors.push(factor);
+            while rest % factor == 0 {
+                rest /= factor;
+            }
+        }
+        factor += 1;
+    }
+    if rest > 1 {
+        factors.push(rest);
+    }
+    (2..prime)
+        .find(|root| {
+            factors
+                .iter()
+                .all(|factor| pow_mod(*root, (prime - 1) / factor, prime) != 1)
+        })
+        .unwrap_or(1)
+}
+
+// The powers `g^q mod prime` of the primitive root `g`, for `q` in `0..prime-1`.
+fn root_powers(prime: usize) -> Vec<usize> {
+    let root = primitive_root(prime);
+    iter::successors(Some(1), |power| Some(mul_mod(*power, root, prime)))
+        .take(prime - 1)
+        .collect()
+}
+
+// Get the length of the cyclic convolution for a prime length.
+// The convolution of length N-1 uses complex FFTs of length (N-1)/2. When that length has prime factors
+// without fast butterflies in RustFFT, the convolution is instead zero-padded to a fast length of at least 2N-3.
+fn convolution_len(prime: usize) -> usize {
+    if has_only_factors((prime - 1) / 2, &[2, 3, 5, 7, 11, 13]) {
+        prime - 1
+    } else {
+        next_fast_len(2 * prime - 3)
+    }
+}
+
+// Iterate over the inverse powers `g^-q mod prime` for `q` in `0..prime-1`, given the powers from `root_powers`.
+fn inverse_powers(powers: &[usize]) -> impl Iterator<Item = &usize> {
+    powers[..1].iter().chain(powers[1..].iter().rev())
+}
+
+// The spectrum of the real-valued kernel `cos(2*pi*g^q/N) - sin(2*pi*g^q/N)` that the reordered data is convolved with,
+// optionally reversed to give a correlation, and multiplied by a scale factor.
+// When the convolution is zero-padded, the kernel is repeated at the end, so that the values
+// up to index N-2 of the padded convolution equal the ones of the cyclic convolution of length N-1.
+fn kernel_spectrum<T: FftNum>(
+    powers: &[usize],
+    reversed: bool,
+    scale: f64,
+    r2c: &RealToComplexEven<T>,
+) -> Vec<Complex<T>> {
+    let length = powers.len() + 1;
+    let angle = 2.0 * std::f64::consts::PI / length as f64;
+    let kernel_value = |power: &usize| {
+        let (sin, cos) = (angle * *power as f64).sin_cos();
+        T::from_f64((cos - sin) * scale).unwrap()
+    };
+    let values: Vec<T> = if reversed {
+        inverse_powers(powers).map(kernel_value).collect()
+    } else {
+        powers.iter().map(kernel_value).collect()
+    };
+    let mut kernel = r2c.make_input_vec();
+    let padding = kernel.len() - values.len();
+    kernel[..values.len()].copy_from_slice(&values);
+    kernel[padding + 1..].copy_from_slice(&values[1..]);
+    let mut spectrum = r2c.make_output_vec();
+    let mut scratch = r2c.make_scratch_vec();
+    r2c.process_with_scratch(&mut kernel, &mut spectrum, &mut scratch)
+        .unwrap();
+    spectrum
+}
+
+/// A Real-to-Complex FFT for prime lengths, using a version of Rader's algorithm for real-valued data.
+///
+/// The values at the indices `1..N` are reordered by the powers of a primitive root of N,
+/// which turns the FFT into a cyclic correlation of length N-1 with a real-valued kernel.
+/// Since N-1 is even, the correlation is calculated using real FFTs of length N-1,
+/// that each use a complex FFT of length (N-1)/2.
+/// If that length is slow to transform, the correlation is zero-padded to a fast length instead.
+pub(crate) struct RealToComplexRader<T> {
+    length: usize,
+    conv_len: usize,
+    powers: Vec<usize>,
+    kernel: Vec<Complex<T>>,
+    r2c: RealToComplexEven<T>,
+    c2r: ComplexToRealEven<T>,
+    scale: T,
+    scratch_len: usize,
+}
+
+impl<T: FftNum> RealToComplexRader<T> {
+    /// Create a new RealToComplex FFT for input data of a given length, that scales the result according to the given normalization.
+    /// Uses the given planner to build the inner FFTs. Panics if the length is not an odd prime.
+    pub(crate) fn new<P: ComplexPlanner<T>>(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut P,
+    ) -> Self {
+        if length % 2 == 0 || !is_prime(length) {
+            panic!("Length must be an odd prime, got {}", length,);
+        }
+        let scale = normalization.forward_scale::<f64>(length);
+        let conv_len = convolution_len(length);
+        let r2c = RealToComplexEven::with_planner(conv_len, Normalization::None, fft_planner);
+        let c2r = ComplexToRealEven::with_planner(conv_len, Normalization::None, fft_planner);
+        let powers = root_powers(length);
+        // The kernel includes the scaling of the inner iFFT and the halving when separating the result
+        let kernel = kernel_spectrum(&powers, true, scale / (2 * conv_len) as f64, &r2c);
+        // Without padding, the spectrum fits in the output
+        let spectrum_len = if conv_len == length - 1 {
+            0
+        } else {
+            conv_len / 2 + 1
+        };
+        let scratch_len =
+            conv_len / 2 + spectrum_len + r2c.get_scratch_len().max(c2r.get_scratch_len());
+        RealToComplexRader {
+            length,
+            conv_len,
+            powers,
+            kernel,
+            r2c,
+            c2r,
+            scale: T::from_f64(scale).unwrap(),
+            scratch_len,
+        }
+    }
+}
+
+impl<T: FftNum> RealToComplex<T> for RealToComplexRader<T> {
+    /// Transform a vector of N real-valued samples, storing the result in the N/2+1 (with N/2 rounded down) element long complex output vector.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    fn process(&self, input: &mut [T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a vector of N real-valued samples, storing the result in the N/2+1 (with N/2 rounded down) element long complex output vector.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.length {
+            return Err(FftError::InputBuffer(self.length, input.len()));
+        }
+        let expected_output_buffer_size = self.length / 2 + 1;
+        if output.len() != expected_output_buffer_size {
+            return Err(FftError::OutputBuffer(
+                expected_output_buffer_size,
+                output.len(),
+            ));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let half_len = self.length / 2;
+        let (buffer, rest) = scratch.split_at_mut(self.conv_len / 2);
+        let buffer = as_real(buffer);
+        for (buf, power) in buffer.iter_mut().zip(self.powers.iter()) {
+            *buf = input[*power];
+        }
+        for buf in buffer[self.length - 1..].iter_mut() {
+            *buf = T::zero();
+        }
+
+        // Correlate with the kernel, using the output for the spectrum when the correlation is not padded
+        let (spectrum, inner_scratch) = if self.conv_len == self.length - 1 {
+            (&mut output[..], rest)
+        } else {
+            rest.split_at_mut(self.conv_len / 2 + 1)
+        };
+        self.r2c
+            .process_with_scratch(buffer, spectrum, inner_scratch)?;
+        for (val, kernel) in spectrum.iter_mut().zip(self.kernel.iter()) {
+            *val = *val * *kernel;
+        }
+        // The first and last values are real, apart from rounding errors
+        let last = spectrum.len() - 1;
+        spectrum[0].im = T::zero();
+        spectrum[last].im = T::zero();
+        self.c2r
+            .process_with_scratch(spectrum, buffer, inner_scratch)?;
+
+        // The correlation at `q` gives the sum of the real and imaginary parts of the value at index `g^-q`,
+        // and the one at `q + (N-1)/2` gives their difference, since `g^((N-1)/2)` equals -1.
+        let first = input[0] * self.scale;
+        let sum = input.iter().fold(T::zero(), |acc, val| acc + *val);
+        output[0] = Complex::new(sum * self.scale, T::zero());
+        let (sums, diffs) = buffer[..self.length - 1].split_at(half_len);
+        for (power, (sum, diff)) in inverse_powers(&self.powers).zip(sums.iter().zip(diffs.iter()))
+        {
+            let value = Complex::new(first + *sum + *diff, *sum - *diff);
+            if *power <= half_len {
+                output[*power] = value;
+            } else {
+                output[self.length - power] = value.conj();
+            }
+        }
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.len() / 2 + 1]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+/// A Complex-to-Real iFFT for prime lengths, using a version of Rader's algorithm for real-valued data.
+///
+/// The sums of the real and imaginary parts of the spectrum at the indices `1..N` are reordered by the inverse powers
+/// of a primitive root of N. This turns the iFFT into a cyclic convolution of length N-1 with a real-valued kernel,
+/// that is calculated using real FFTs of length N-1 like for `RealToComplexRader`.
+pub(crate) struct ComplexToRealRader<T> {
+    length: usize,
+    conv_len: usize,
+    powers: Vec<usize>,
+    kernel: Vec<Complex<T>>,
+    r2c: RealToComplexEven<T>,
+    c2r: ComplexToRealEven<T>,
+    scale: T,
+    scratch_len: usize,
+}
+
+impl<T: FftNum> ComplexToRealRader<T> {
+    /// Create a new ComplexToReal FFT for output data of a given length, that scales the result according to the given normalization.
+    /// Uses the given planner to build the inner FFTs. Panics if the length is not an odd prime.
+    pub(crate) fn new<P: ComplexPlanner<T>>(
+        length: usize,
+        normalization: Normalization,
+        fft_planner: &mut P,
+    ) -> Self {
+        if length % 2 == 0 || !is_prime(length) {
+            panic!("Length must be an odd prime, got {}", length,);
+        }
+        let scale = normalization.inverse_scale::<f64>(length);
+        let conv_len = convolution_len(length);
+        let r2c = RealToComplexEven::with_planner(conv_len, Normalization::None, fft_planner);
+        let c2r = ComplexToRealEven::with_planner(conv_len, Normalization::None, fft_planner);
+        let powers = root_powers(length);
+        // The kernel includes the scaling of the inner iFFT
+        let kernel = kernel_spectrum(&powers, false, scale / conv_len as f64, &r2c);
+        // Without padding, the spectrum fits in the input
+        let spectrum_len = if conv_len == length - 1 {
+            0
+        } else {
+            conv_len / 2 + 1
+        };
+        let scratch_len =
+            conv_len / 2 + spectrum_len + r2c.get_scratch_len().max(c2r.get_scratch_len());
+        ComplexToRealRader {
+            length,
+            conv_len,
+            powers,
+            kernel,
+            r2c,
+            c2r,
+            scale: T::from_f64(scale).unwrap(),
+            scratch_len,
+        }
+    }
+}
+
+impl<T: FftNum> ComplexToReal<T> for ComplexToRealRader<T> {
+    /// Transform a complex spectrum of N/2+1 (with N/2 rounded down) values and store the real result in the N long output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// If the imaginary part of the first value is not zero, it is ignored and the transform is still performed.
+    /// The function then returns an `FftError::InputValues` error to tell that the result may not be correct.
+    fn process(&self, input: &mut [Complex<T>], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a complex spectrum of N/2+1 (with N/2 rounded down) values and store the real result in the N long output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which will be faster if it is called more than once.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// If the imaginary part of the first value is not zero, it is ignored and the transform is still performed.
+    /// The function then returns an `FftError::InputValues` error to tell that the result may not be correct.
+    fn process_with_scratch(
+        &self,
+        input: &mut [Complex<T>],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let expected_input_buffer_size = self.length / 2 + 1;
+        if input.len() != expected_input_buffer_size {
+            return Err(FftError::InputBuffer(
+                expected_input_buffer_size,
+                input.len(),
+            ));
+        }
+        if output.len() != self.length {
+            return Err(FftError::OutputBuffer(self.length, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let first_invalid = input[0].im != T::zero();
+        let half_len = self.length / 2;
+        let (buffer, rest) = scratch.split_at_mut(self.conv_len / 2);
+        let buffer = as_real(buffer);
+        // The values at `g^-q` and `g^-(q + (N-1)/2)` are complex conjugates, since `g^((N-1)/2)` equals -1.
+        // Using the sum of the real and imaginary parts gives a real-valued sequence that contains both.
+        for (buf, power) in buffer.iter_mut().zip(inverse_powers(&self.powers)) {
+            let value = if *power <= half_len {
+                input[*power]
+            } else {
+                input[self.length - power].conj()
+            };
+            *buf = value.re + value.im;
+        }
+        for buf in buffer[self.length - 1..].iter_mut() {
+            *buf = T::zero();
+        }
+        let first = input[0].re * self.scale;
+        let sum = input
+            .iter()
+            .skip(1)
+            .fold(input[0].re, |acc, val| acc + val.re + val.re);
+
+        // Convolve with the kernel, using the input for the spectrum when the convolution is not padded
+        let (spectrum, inner_scratch) = if self.conv_len == self.length - 1 {
+            (&mut input[..], rest)
+        } else {
+            rest.split_at_mut(self.conv_len / 2 + 1)
+        };
+        self.r2c
+            .process_with_scratch(buffer, spectrum, inner_scratch)?;
+        for (val, kernel) in spectrum.iter_mut().zip(self.kernel.iter()) {
+            *val = *val * *kernel;
+        }
+        // The first and last values are real, apart from rounding errors
+        let last = spectrum.len() - 1;
+        spectrum[0].im = T::zero();
+        spectrum[last].im = T::zero();
+        self.c2r
+            .process_with_scratch(spectrum, buffer, inner_scratch)?;
+
+        output[0] = sum * self.scale;
+        for (val, power) in buffer.iter().zip(self.powers.iter()) {
+            output[*power] = first + *val;
+        }
+        if first_invalid {
+            return Err(FftError::InputValues(true, false));
+        }
+        Ok(())
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn make_input_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.len() / 2 + 1]
+    }
+
+    fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{ComplexToReal, FftError, Normalization, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::sync::Arc;
+
+/// A resampler that changes the length of a signal by zero-padding or truncating its spectrum,
+/// with the same results as `scipy.signal.resample`.
+///
+/// The signal is transformed with a real-to-complex FFT of the input length, and the spectrum is then transformed back
+/// with a complex-to-real iFFT of the output length. The signal is assumed to be periodic,
+/// and the result is the band-limited interpolation of it at the new sample positions.
+///
+/// When the shorter of the two lengths is even, the last value of its spectrum is the Nyquist frequency,
+/// which represents both the positive and the negative frequency.
+/// When upsampling, the Nyquist value of the input is split equally between the positive and negative frequencies.
+/// When downsampling, the values at the positive and negative frequencies that end up at the Nyquist frequency
+/// of the output are added, which for a real signal gives twice the real part of the positive one.
+///
+/// The result is scaled by `1/input_len`, so that the amplitude of the signal is preserved.
+pub struct FftResampler<T> {
+    input_len: usize,
+    output_len: usize,
+    fft: Arc<dyn RealToComplex<T>>,
+    ifft: Arc<dyn ComplexToReal<T>>,
+    scratch_len: usize,
+}
+
+impl<T: FftNum> FftResampler<T> {
+    /// Create a new resampler from the given input length to the given output length.
+    /// Uses the given RealFftPlanner to build the FFT and iFFT.
+    /// Panics if any of the lengths is zero.
+    pub fn new(input_len: usize, output_len: usize, planner: &mut RealFftPlanner<T>) -> Self {
+        if input_len == 0 || output_len == 0 {
+            panic!("The input and output lengths must be at least 1");
+        }
+        let fft = planner.plan_fft_forward_normalized(input_len, Normalization::Forward);
+        let ifft = planner.plan_fft_inverse(output_len);
+        let spectrum_len = input_len.max(output_len) / 2 + 1;
+        let scratch_len = spectrum_len + fft.get_scratch_len().max(ifft.get_scratch_len());
+        FftResampler {
+            input_len,
+            output_len,
+            fft,
+            ifft,
+            scratch_len,
+        }
+    }
+
+    /// Resample the input, storing the result in the output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if the input or output has the wrong length.
+    pub fn process(&self, input: &mut [T], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Resample the input, storing the result in the output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector instead of allocating, which makes this the faster of the two.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.input_len {
+            return Err(FftError::InputBuffer(self.input_len, input.len()));
+        }
+        if output.len() != self.output_len {
+            return Err(FftError::OutputBuffer(self.output_len, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let input_bins = self.input_len / 2 + 1;
+        let output_bins = self.output_len / 2 + 1;
+        let spectrum_len = input_bins.max(output_bins);
+        let (spectrum, fft_scratch) = scratch.split_at_mut(spectrum_len);
+        self.fft.process_with_scratch(
+            input,
+            &mut spectrum[..input_bins],
+            &mut fft_scratch[..self.fft.get_scratch_len()],
+        )?;
+        for val in spectrum[input_bins..].iter_mut() {
+            *val = Complex::zero();
+        }
+        // The first value is real, apart from rounding errors.
+        spectrum[0].im = T::zero();
+
+        let min_len = self.input_len.min(self.output_len);
+        if min_len % 2 == 0 {
+            let nyquist = &mut spectrum[min_len / 2];
+            if self.output_len < self.input_len {
+                *nyquist = Complex::new(nyquist.re + nyquist.re, T::zero());
+            } else if self.output_len > self.input_len {
+                *nyquist = *nyquist * T::from_f64(0.5).unwrap();
+            }
+        }
+        self.ifft.process_with_scratch(
+            &mut spectrum[..output_bins],
+            output,
+            &mut fft_scratch[..self.ifft.get_scratch_len()],
+        )
+    }
+
+    /// Get the length of the input signals.
+    pub fn input_len(&self) -> usize {
+        self.input_len
+    }
+
+    /// Get the length of the resampled signals.
+    pub fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Convenience method to make an input vector of the right type and length.
+    pub fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.input_len]
+    }
+
+    /// Convenience method to make an output vector of the right type and length.
+    pub fn make_output_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.output_len]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FftError, FftResampler, RealFftPlanner};
+    use std::f64::consts::PI;
+
+    // Resample a band-limited signal, and compare with the signal evaluated at the new sample positions
+    fn check_resampled<F: Fn(f64) -> f64>(input_len: usize, output_len: usize, signal: F) {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let resampler = FftResampler::new(input_len, output_len, &mut planner);
+        let mut input: Vec<f64> = (0..input_len).map(|n| signal(n as f64)).collect();
+        let mut output = resampler.make_output_vec();
+        resampler.process(&mut input, &mut output).unwrap();
+        let step = input_len as f64 / output_len as f64;
+        for (n, val) in output.iter().enumerate() {
+            let expected = signal(n as f64 * step);
+            assert!(
+                (val - expected).abs() < 1.0e-9,
+                "{} -> {}, {}: {} {}",
+                input_len,
+                output_len,
+                n,
+                val,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn resample() {
+        for &(input_len, output_len) in [(16, 24), (16, 10), (15, 22), (15, 10), (12, 12)].iter() {
+            let len = input_len as f64;
+            check_resampled(input_len, output_len, |t| {
+                0.5 + (2.0 * PI * 2.0 * t / len).cos()
+                    - 0.7 * (2.0 * PI * 3.0 * t / len + 0.2).sin()
+            });
+        }
+
+        // Upsampling splits the Nyquist value, which gives a cosine
+        check_resampled(8, 16, |t| (PI * t).cos());
+        check_resampled(8, 13, |t| (PI * t).cos());
+
+        // Downsampling keeps the real part at the new Nyquist frequency
+        let mut planner = RealFftPlanner::<f64>::new();
+        let resampler = FftResampler::new(16, 8, &mut planner);
+        let mut input: Vec<f64> = (0..16)
+            .map(|n| (2.0 * PI * (4 * n) as f64 / 16.0 + 0.4).cos())
+            .collect();
+        let mut output = resampler.make_output_vec();
+        resampler.process(&mut input, &mut output).unwrap();
+        for (n, val) in output.iter().enumerate() {
+            let expected = if n % 2 == 0 {
+                0.4f64.cos()
+            } else {
+                -0.4f64.cos()
+            };
+            assert!((val - expected).abs() < 1.0e-9);
+        }
+
+        let res = resampler.process(&mut input, &mut output[..7]);
+        assert!(matches!(res, Err(FftError::OutputBuffer(8, 7))));
+        let res = resampler.process(&mut input[..15], &mut output);
+        assert!(matches!(res, Err(FftError::InputBuffer(16, 15))));
+    }
+}
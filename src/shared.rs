@@ -0,0 +1,246 @@
+use crate::{
+    build_fft_forward, build_fft_inverse, default_algorithm, ComplexToReal, Normalization,
+    RealToComplex,
+};
+use rustfft::{FftNum, FftPlanner};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+
+// The number of independently locked parts of each cache.
+const SHARDS: usize = 16;
+
+type Key = (usize, Normalization);
+
+// A cache split into shards by length, so that threads planning different lengths rarely wait for each other.
+struct ShardedCache<V> {
+    shards: Vec<RwLock<HashMap<Key, V>>>,
+}
+
+impl<V: Clone> ShardedCache<V> {
+    fn new() -> Self {
+        ShardedCache {
+            shards: (0..SHARDS).map(|_| RwLock::new(HashMap::new())).collect(),
+        }
+    }
+
+    // Get the cached value, or build and store a new one.
+    // The shard stays locked while building, so that all threads get the same value.
+    fn get_or_insert_with<F: FnOnce() -> V>(&self, key: Key, build: F) -> V {
+        let shard = &self.shards[key.0 % SHARDS];
+        // A panic in another thread can't leave a map in an inconsistent state, so poisoned locks are used anyway.
+        if let Some(value) = shard
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&key)
+        {
+            return value.clone();
+        }
+        let mut map = shard
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(value) = map.get(&key) {
+            return value.clone();
+        }
+        let value = build();
+        map.insert(key, value.clone());
+        value
+    }
+
+    // Remove the values for the length, for any normalization.
+    fn remove(&self, len: usize) {
+        self.shards[len % SHARDS]
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .retain(|key, _| key.0 != len);
+    }
+
+    fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clear();
+        }
+    }
+
+    fn lengths(&self) -> Vec<usize> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                shard
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .keys()
+                    .map(|key| key.0)
+                    .collect::<Vec<usize>>()
+            })
+            .collect()
+    }
+}
+
+/// A planner that can be shared between threads, for example in an `Arc` or as a `static`.
+///
+/// It works like `RealFftPlanner`, but the planning methods take `&self` instead of `&mut self`.
+/// The caches are split into shards that are locked independently, so threads planning FFTs of different lengths
+/// are mostly not blocked by each other. Looking up an FFT that is already planned only needs a read lock.
+/// Threads planning the same length get references to the same instance.
+///
+/// Unlike `RealFftPlanner`, the caches have no capacity limit, and FFTs are only removed by `remove` and `clear`.
+/// The inner complex FFTs are kept by the inner complex planner until `clear` is called.
+pub struct SharedRealFftPlanner<T: FftNum> {
+    planner: Mutex<FftPlanner<T>>,
+    r2c_cache: ShardedCache<Arc<dyn RealToComplex<T>>>,
+    c2r_cache: ShardedCache<Arc<dyn ComplexToReal<T>>>,
+}
+
+impl<T: FftNum> SharedRealFftPlanner<T> {
+    /// Create a new planner.
+    pub fn new() -> Self {
+        Self {
+            planner: Mutex::new(FftPlanner::new()),
+            r2c_cache: ShardedCache::new(),
+            c2r_cache: ShardedCache::new(),
+        }
+    }
+
+    /// Plan a Real-to-Complex forward FFT. Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same length, this will return a new reference to the already existing one.
+    pub fn plan_fft_forward(&self, len: usize) -> Arc<dyn RealToComplex<T>> {
+        self.plan_fft_forward_normalized(len, Normalization::None)
+    }
+
+    /// Plan a Real-to-Complex forward FFT that scales the result according to the given normalization.
+    /// Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same length and normalization, this will return a new reference to the already existing one.
+    pub fn plan_fft_forward_normalized(
+        &self,
+        len: usize,
+        normalization: Normalization,
+    ) -> Arc<dyn RealToComplex<T>> {
+        self.r2c_cache.get_or_insert_with((len, normalization), || {
+            build_fft_forward(
+                len,
+                default_algorithm(len),
+                normalization,
+                &mut *self.lock_planner(),
+            )
+        })
+    }
+
+    /// Plan a Complex-to-Real inverse FFT. Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same length, this will return a new reference to the already existing one.
+    pub fn plan_fft_inverse(&self, len: usize) -> Arc<dyn ComplexToReal<T>> {
+        self.plan_fft_inverse_normalized(len, Normalization::None)
+    }
+
+    /// Plan a Complex-to-Real inverse FFT that scales the result according to the given normalization.
+    /// Returns the FFT in a shared reference.
+    /// If requesting a second FFT of the same length and normalization, this will return a new reference to the already existing one.
+    pub fn plan_fft_inverse_normalized(
+        &self,
+        len: usize,
+        normalization: Normalization,
+    ) -> Arc<dyn ComplexToReal<T>> {
+        self.c2r_cache.get_or_insert_with((len, normalization), || {
+            build_fft_inverse(
+                len,
+                default_algorithm(len),
+                normalization,
+                &mut *self.lock_planner(),
+            )
+        })
+    }
+
+    /// Remove all FFTs of the given length from the caches, for any normalization.
+    pub fn remove(&self, len: usize) {
+        self.r2c_cache.remove(len);
+        self.c2r_cache.remove(len);
+    }
+
+    /// Remove all FFTs from the caches, including the inner complex FFTs.
+    /// FFTs planned after this don't share any inner FFTs with the ones planned before.
+    pub fn clear(&self) {
+        self.r2c_cache.clear();
+        self.c2r_cache.clear();
+        *self.lock_planner() = FftPlanner::new();
+    }
+
+    /// Get the sorted lengths of all FFTs in the caches.
+    pub fn cached_lengths(&self) -> Vec<usize> {
+        let mut lengths = self.r2c_cache.lengths();
+        lengths.extend(self.c2r_cache.lengths());
+        lengths.sort_unstable();
+        lengths.dedup();
+        lengths
+    }
+
+    // Lock the inner complex planner. No shard is ever locked while holding this lock, to avoid deadlocks.
+    fn lock_planner(&self) -> MutexGuard<'_, FftPlanner<T>> {
+        self.planner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T: FftNum> Default for SharedRealFftPlanner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComplexToReal, RealFftPlanner, RealToComplex, SharedRealFftPlanner};
+    use std::sync::Arc;
+    use std::thread;
+
+    // Plan the same lengths from several threads, and check that they all get the same instances
+    #[test]
+    fn shared_planner() {
+        let planner = Arc::new(SharedRealFftPlanner::<f64>::new());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let planner = Arc::clone(&planner);
+                thread::spawn(move || {
+                    (90..110)
+                        .map(|len| (planner.plan_fft_forward(len), planner.plan_fft_inverse(len)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        for (idx, (r2c, c2r)) in results[0].iter().enumerate() {
+            for other in results[1..].iter() {
+                assert_eq!(
+                    &**r2c as *const dyn RealToComplex<f64> as *const u8,
+                    &*other[idx].0 as *const dyn RealToComplex<f64> as *const u8
+                );
+                assert_eq!(
+                    &**c2r as *const dyn ComplexToReal<f64> as *const u8,
+                    &*other[idx].1 as *const dyn ComplexToReal<f64> as *const u8
+                );
+            }
+        }
+
+        // The results are the same as for the normal planner
+        let mut real_planner = RealFftPlanner::<f64>::new();
+        let shared_fft = planner.plan_fft_forward(101);
+        let fft = real_planner.plan_fft_forward(101);
+        let mut input: Vec<f64> = (0..101).map(|n| (n % 7) as f64).collect();
+        let mut output_shared = shared_fft.make_output_vec();
+        let mut output = fft.make_output_vec();
+        shared_fft
+            .process(&mut input.clone(), &mut output_shared)
+            .unwrap();
+        fft.process(&mut input, &mut output).unwrap();
+        assert_eq!(output_shared, output);
+
+        assert_eq!(planner.cached_lengths(), (90..110).collect::<Vec<usize>>());
+        planner.remove(101);
+        planner.plan_fft_inverse(200);
+        assert_eq!(planner.cached_lengths().len(), 20);
+        assert!(!planner.cached_lengths().contains(&101));
+        planner.clear();
+        assert!(planner.cached_lengths().is_empty());
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{as_real, ComplexToReal, FftError, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::sync::Arc;
+
+/// Selects how the signal is padded before it is split into frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingMode {
+    /// No padding. The first frame starts at the first sample, and samples after the last complete frame are dropped.
+    None,
+    /// Pad with `frame_len/2` zeros at both ends, so that each frame is centered at a multiple of the hop length.
+    Zeros,
+    /// Pad with `frame_len/2` samples at both ends, so that each frame is centered at a multiple of the hop length.
+    /// The padding is the signal mirrored around the first and last samples, without repeating them.
+    Reflect,
+}
+
+/// A Short-Time Fourier Transform, that splits a real-valued signal into overlapping windowed frames
+/// and transforms each frame with a real-to-complex FFT.
+///
+/// The result is a spectrogram of `frames` x `frame_len/2+1` complex values, stored frame by frame.
+/// Like the FFTs, the result is not normalized.
+pub struct Stft<T> {
+    frame_len: usize,
+    hop_len: usize,
+    window: Vec<T>,
+    padding: PaddingMode,
+    fft: Arc<dyn RealToComplex<T>>,
+    scratch_len: usize,
+}
+
+/// An inverse Short-Time Fourier Transform, that transforms each frame of a spectrogram with a complex-to-real iFFT,
+/// and combines the frames using windowed overlap-add.
+///
+/// The sum is normalized by the sum of the squared windows at each sample, and the iFFTs are scaled by `1/frame_len`.
+/// This gives perfect reconstruction of a signal transformed with an `Stft` using the same parameters,
+/// as long as the frames overlap enough that the sum of the squared windows is non-zero at each sample.
+/// Samples where the sum is zero are set to zero.
+pub struct Istft<T> {
+    frame_len: usize,
+    hop_len: usize,
+    window: Vec<T>,
+    padding: PaddingMode,
+    fft: Arc<dyn ComplexToReal<T>>,
+    scratch_len: usize,
+}
+
+fn check_parameters<T>(frame_len: usize, hop_len: usize, window: &[T]) {
+    if frame_len == 0 || hop_len == 0 {
+        panic!("The frame and hop lengths must be at least 1");
+    }
+    if window.len() != frame_len {
+        panic!("Window length must be {}, got {}", frame_len, window.len());
+    }
+}
+
+// Get the number of samples added at the start of the signal.
+fn padding_len(frame_len: usize, padding: PaddingMode) -> usize {
+    match padding {
+        PaddingMode::None => 0,
+        PaddingMode::Zeros | PaddingMode::Reflect => frame_len / 2,
+    }
+}
+
+// Get the number of frames for a signal of the given length.
+fn frame_count(signal_len: usize, frame_len: usize, hop_len: usize, padding: PaddingMode) -> usize {
+    let padded_len = signal_len + 2 * padding_len(frame_len, padding);
+    if signal_len == 0 || padded_len < frame_len {
+        return 0;
+    }
+    1 + (padded_len - frame_len) / hop_len
+}
+
+// Get the sample at a position in the padded signal, where the position may be outside of the signal.
+fn padded_sample<T: FftNum>(signal: &[T], position: isize, padding: PaddingMode) -> T {
+    let len = signal.len() as isize;
+    if position >= 0 && position < len {
+        return signal[position as usize];
+    }
+    match padding {
+        PaddingMode::Reflect if len > 1 => {
+            let period = 2 * (len - 1);
+            let mut idx = position % period;
+            if idx < 0 {
+                idx += period;
+            }
+            if idx >= len {
+                idx = period - idx;
+            }
+            signal[idx as usize]
+        }
+        PaddingMode::Reflect => signal[0],
+        _ => T::zero(),
+    }
+}
+
+impl<T: FftNum> Stft<T> {
+    /// Create a new STFT with the given frame length, hop length, window and padding.
+    /// Uses the given RealFftPlanner to build the FFT.
+    /// Panics if the frame or hop length is zero, or if the window does not have the same length as the frames.
+    pub fn new(
+        frame_len: usize,
+        hop_len: usize,
+        window: Vec<T>,
+        padding: PaddingMode,
+        planner: &mut RealFftPlanner<T>,
+    ) -> Self {
+        check_parameters(frame_len, hop_len, &window);
+        let fft = planner.plan_fft_forward(frame_len);
+        let scratch_len = (frame_len + 1) / 2 + fft.get_scratch_len();
+        Stft {
+            frame_len,
+            hop_len,
+            window,
+            padding,
+            fft,
+            scratch_len,
+        }
+    }
+
+    /// Get the number of frames of the spectrogram for a signal of the given length.
+    pub fn frames(&self, signal_len: usize) -> usize {
+        frame_count(signal_len, self.frame_len, self.hop_len, self.padding)
+    }
+
+    /// Get the number of frequency bins of each frame, `frame_len/2+1`.
+    pub fn bins(&self) -> usize {
+        self.frame_len / 2 + 1
+    }
+
+    /// Transform a signal, storing the spectrogram in the output.
+    /// It allocates scratch space as needed.
+    /// An error is returned if the output does not have the length `frames` x `bins`.
+    pub fn process(&self, signal: &[T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(signal, output, &mut scratch)
+    }
+
+    /// Transform a signal, storing the spectrogram in the output.
+    /// It uses the provided scratch vector for all frames, instead of allocating.
+    /// An error is returned if any of the given slices has the wrong length.
+    pub fn process_with_scratch(
+        &self,
+        signal: &[T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let bins = self.bins();
+        let expected_output_len = self.frames(signal.len()) * bins;
+        if output.len() != expected_output_len {
+            return Err(FftError::OutputBuffer(expected_output_len, output.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let (frame, fft_scratch) = scratch.split_at_mut((self.frame_len + 1) / 2);
+        let frame = &mut as_real(frame)[..self.frame_len];
+        let pad = padding_len(self.frame_len, self.padding) as isize;
+        for (idx, spectrum) in output.chunks_exact_mut(bins).enumerate() {
+            let start = (idx * self.hop_len) as isize - pad;
+            for (n, (val, w)) in frame.iter_mut().zip(self.window.iter()).enumerate() {
+                *val = padded_sample(signal, start + n as isize, self.padding) * *w;
+            }
+            self.fft
+                .process_with_scratch(frame, spectrum, fft_scratch)?;
+        }
+        Ok(())
+    }
+
+    /// Get the window that is applied to the frames.
+    pub fn window(&self) -> &[T] {
+        &self.window
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Convenience method to make an output vector of the right length for a signal of the given length.
+    pub fn make_output_vec(&self, signal_len: usize) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.frames(signal_len) * self.bins()]
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+impl<T: FftNum> Istft<T> {
+    /// Create a new ISTFT with the given frame length, hop length, window and padding.
+    /// Uses the given RealFftPlanner to build the iFFT.
+    /// Panics if the frame or hop length is zero, or if the window does not have the same length as the frames.
+    pub fn new(
+        frame_len: usize,
+        hop_len: usize,
+        window: Vec<T>,
+        padding: PaddingMode,
+        planner: &mut RealFftPlanner<T>,
+    ) -> Self {
+        check_parameters(frame_len, hop_len, &window);
+        let fft = planner.plan_fft_inverse(frame_len);
+        let scratch_len = (frame_len + 1) / 2 + fft.get_scratch_len();
+        Istft {
+            frame_len,
+            hop_len,
+            window,
+            padding,
+            fft,
+            scratch_len,
+        }
+    }
+
+    /// Get the number of frequency bins of each frame, `frame_len/2+1`.
+    pub fn bins(&self) -> usize {
+        self.frame_len / 2 + 1
+    }
+
+    /// Transform a spectrogram of `frames` x `bins` values, storing the reconstructed signal in the output.
+    /// The length of the signal is given by the length of the output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also allocates additional scratch space as needed.
+    /// An error is returned if the length of the input is not a multiple of the number of bins.
+    /// The imaginary parts of the first and last bins that should be zero are ignored, and no error is returned for them.
+    pub fn process(&self, input: &mut [Complex<T>], output: &mut [T]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    /// Transform a spectrogram of `frames` x `bins` values, storing the reconstructed signal in the output.
+    /// The length of the signal is given by the length of the output.
+    /// The input buffer is used as scratch space, so the contents of input should be considered garbage after calling.
+    /// It also uses the provided scratch vector for all frames, instead of allocating.
+    /// An error is returned if any of the given slices has the wrong length.
+    /// The imaginary parts of the first and last bins that should be zero are ignored, and no error is returned for them.
+    pub fn process_with_scratch(
+        &self,
+        input: &mut [Complex<T>],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        let bins = self.bins();
+        let frames = input.len() / bins;
+        if input.len() != frames * bins {
+            return Err(FftError::InputBuffer(frames * bins, input.len()));
+        }
+        if scratch.len() < self.scratch_len {
+            return Err(FftError::ScratchBuffer(self.scratch_len, scratch.len()));
+        }
+        let (frame, fft_scratch) = scratch.split_at_mut((self.frame_len + 1) / 2);
+        let frame = &mut as_real(frame)[..self.frame_len];
+        let pad = padding_len(self.frame_len, self.padding) as isize;
+        let scale = T::one() / T::from_usize(self.frame_len).unwrap();
+        for val in output.iter_mut() {
+            *val = T::zero();
+        }
+        for (idx, spectrum) in input.chunks_exact_mut(bins).enumerate() {
+            spectrum[0].im = T::zero();
+            if self.frame_len % 2 == 0 {
+                spectrum[bins - 1].im = T::zero();
+            }
+            self.fft
+                .process_with_scratch(spectrum, frame, fft_scratch)?;
+            let start = (idx * self.hop_len) as isize - pad;
+            for (n, (val, w)) in frame.iter().zip(self.window.iter()).enumerate() {
+                let position = start + n as isize;
+                if position >= 0 && (position as usize) < output.len() {
+                    let out = &mut output[position as usize];
+                    *out = *out + *val * *w * scale;
+                }
+            }
+        }
+
+        // Normalize by the sum of the squared windows of the frames that overlap each sample
+        for (position, val) in output.iter_mut().enumerate() {
+            let padded = position + pad as usize;
+            let first = (padded + self.hop_len).saturating_sub(self.frame_len) / self.hop_len;
+            let last = (padded / self.hop_len).min(frames.saturating_sub(1));
+            let mut window_sum = T::zero();
+            for frame_idx in first..=last {
+                if frame_idx >= frames {
+                    break;
+                }
+                let w = self.window[padded - frame_idx * self.hop_len];
+                window_sum = window_sum + w * w;
+            }
+            if (window_sum - T::from_f64(1.0e-10).unwrap()).is_positive() {
+                *val = *val / window_sum;
+            } else {
+                *val = T::zero();
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the window that is applied to the frames.
+    pub fn window(&self) -> &[T] {
+        &self.window
+    }
+
+    /// Get the length of the scratch space needed for `process_with_scratch`.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Convenience method to make a scratch vector of the right type and length.
+    pub fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Istft, PaddingMode, RealFftPlanner, Stft};
+    use rand::Rng;
+
+    fn hann(len: usize) -> Vec<f64> {
+        (0..len)
+            .map(|n| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * n as f64 / len as f64).cos())
+            .collect()
+    }
+
+    // Compare the frames with separately transformed windowed frames
+    #[test]
+    fn stft() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut rng = rand::thread_rng();
+        let signal = (0..100).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+        let window = hann(16);
+        let fft = planner.plan_fft_forward(16);
+        let stft = Stft::new(16, 4, window.clone(), PaddingMode::Reflect, &mut planner);
+        assert_eq!(stft.frames(100), 26);
+        let mut spectrogram = stft.make_output_vec(signal.len());
+        stft.process(&signal, &mut spectrogram).unwrap();
+        let mut padded = signal[1..9].iter().rev().cloned().collect::<Vec<f64>>();
+        padded.extend_from_slice(&signal);
+        padded.extend(signal[91..99].iter().rev());
+        for (idx, frame) in spectrogram.chunks(stft.bins()).enumerate() {
+            let mut windowed = padded[4 * idx..4 * idx + 16]
+                .iter()
+                .zip(window.iter())
+                .map(|(x, w)| x * w)
+                .collect::<Vec<f64>>();
+            let mut expected = fft.make_output_vec();
+            fft.process(&mut windowed, &mut expected).unwrap();
+            for (e, f) in expected.iter().zip(frame.iter()) {
+                assert!((e - f).norm() < 1.0e-9, "Frame: {}, too large error", idx);
+            }
+        }
+    }
+
+    // Check that an STFT followed by an ISTFT gives back the original signal
+    #[test]
+    fn stft_istft() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut rng = rand::thread_rng();
+        let signal = (0..200).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+        for &padding in [PaddingMode::None, PaddingMode::Zeros, PaddingMode::Reflect].iter() {
+            for &(frame_len, hop_len) in [(16, 4), (32, 8), (15, 5)].iter() {
+                let window = hann(frame_len);
+                let stft = Stft::new(frame_len, hop_len, window.clone(), padding, &mut planner);
+                let istft = Istft::new(frame_len, hop_len, window, padding, &mut planner);
+                let mut spectrogram = stft.make_output_vec(signal.len());
+                stft.process(&signal, &mut spectrogram).unwrap();
+                let mut output = vec![0.0; signal.len()];
+                istft.process(&mut spectrogram, &mut output).unwrap();
+                // Without padding, the first and last samples are only covered by the edges of the windows
+                let (start, end) = match padding {
+                    PaddingMode::None => (frame_len, signal.len() - frame_len),
+                    _ => (0, signal.len()),
+                };
+                for n in start..end {
+                    let diff = (signal[n] - output[n]).abs();
+                    assert!(
+                        diff < 1.0e-9,
+                        "Padding: {:?}, frame: {}, sample: {}, too large error: {}",
+                        padding,
+                        frame_len,
+                        n,
+                        diff
+                    );
+                }
+            }
+        }
+    }
+}
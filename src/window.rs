@@ -0,0 +1,297 @@
+use crate::{FftError, RealFftPlanner, RealToComplex, Res};
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FftNum;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// Window functions for spectral analysis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    /// All ones.
+    Rectangular,
+    /// Hann window, `0.5 - 0.5 * cos(2*pi*n/D)`.
+    Hann,
+    /// Hamming window, `0.54 - 0.46 * cos(2*pi*n/D)`.
+    Hamming,
+    /// Blackman window, `0.42 - 0.5 * cos(2*pi*n/D) + 0.08 * cos(4*pi*n/D)`.
+    Blackman,
+    /// Kaiser window with the given shape parameter `beta`.
+    Kaiser(f64),
+    /// Flat-top window, with the same coefficients as `scipy.signal.windows.flattop`.
+    /// The amplitude of a sinusoid is accurate even when it falls between two bins.
+    FlatTop,
+}
+
+/// A forward FFT that multiplies the input by a window before transforming it.
+///
+/// The window is applied in place to the input, which is used as scratch space by the FFT anyway.
+/// It also provides the correction factors needed for amplitude-calibrated spectra.
+pub struct WindowedRealToComplex<T> {
+    fft: Arc<dyn RealToComplex<T>>,
+    window: Vec<T>,
+}
+
+// Zeroth order modified Bessel function of the first kind, calculated from its power series.
+fn bessel_i0(x: f64) -> f64 {
+    let quarter_x_sq = x * x / 4.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1.0e-17 {
+        term *= quarter_x_sq / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+impl Window {
+    // Calculate the window value at position `n`, for a period of `period` samples.
+    fn value(&self, n: usize, period: usize) -> f64 {
+        let cosine_sum = |coeffs: &[f64]| {
+            coeffs
+                .iter()
+                .enumerate()
+                .map(|(k, a)| {
+                    let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * a * (2.0 * PI * (k * n) as f64 / period as f64).cos()
+                })
+                .sum::<f64>()
+        };
+        match self {
+            Window::Rectangular => 1.0,
+            Window::Hann => cosine_sum(&[0.5, 0.5]),
+            Window::Hamming => cosine_sum(&[0.54, 0.46]),
+            Window::Blackman => cosine_sum(&[0.42, 0.5, 0.08]),
+            Window::FlatTop => cosine_sum(&[
+                0.215_578_95,
+                0.416_631_58,
+                0.277_263_158,
+                0.083_578_947,
+                0.006_947_368,
+            ]),
+            Window::Kaiser(beta) => {
+                let ratio = 2.0 * n as f64 / period as f64 - 1.0;
+                bessel_i0(beta * (1.0 - ratio * ratio).max(0.0).sqrt()) / bessel_i0(*beta)
+            }
+        }
+    }
+
+    /// Make a periodic window of the given length, intended for spectral analysis.
+    /// This is the first `len` values of a symmetric window of length `len + 1`.
+    pub fn periodic<T: FftNum>(&self, len: usize) -> Vec<T> {
+        (0..len)
+            .map(|n| T::from_f64(self.value(n, len)).unwrap())
+            .collect()
+    }
+
+    /// Make a symmetric window of the given length, intended for filter design.
+    pub fn symmetric<T: FftNum>(&self, len: usize) -> Vec<T> {
+        if len == 1 {
+            return vec![T::one()];
+        }
+        (0..len)
+            .map(|n| T::from_f64(self.value(n, len - 1)).unwrap())
+            .collect()
+    }
+}
+
+impl<T: FftNum> WindowedRealToComplex<T> {
+    /// Create a new windowed FFT for the given window, and uses the given RealFftPlanner to build the inner FFT.
+    /// The length of the FFT is the length of the window.
+    pub fn new(window: Vec<T>, planner: &mut RealFftPlanner<T>) -> Self {
+        let fft = planner.plan_fft_forward(window.len());
+        WindowedRealToComplex { fft, window }
+    }
+
+    /// Get the window that is applied to the input.
+    pub fn window(&self) -> &[T] {
+        &self.window
+    }
+
+    /// Get the coherent gain of the window, `sum(w) / N`.
+    /// Dividing the magnitude of a spectrum by `N` times the coherent gain gives the amplitude of a sinusoid at the center of a bin,
+    /// after doubling all bins except the first and, for even lengths, the last.
+    pub fn coherent_gain(&self) -> T {
+        let sum = self.window.iter().fold(T::zero(), |acc, w| acc + *w);
+        sum / T::from_usize(self.window.len()).unwrap()
+    }
+
+    /// Get the equivalent noise bandwidth of the window in bins, `N * sum(w^2) / sum(w)^2`.
+    /// Multiply by the bin width to get the bandwidth in Hz.
+    pub fn enbw(&self) -> T {
+        let sum = self.window.iter().fold(T::zero(), |acc, w| acc + *w);
+        let sum_sq = self.window.iter().fold(T::zero(), |acc, w| acc + *w * *w);
+        T::from_usize(self.window.len()).unwrap() * sum_sq / (sum * sum)
+    }
+}
+
+impl<T: FftNum> RealToComplex<T> for WindowedRealToComplex<T> {
+    fn process(&self, input: &mut [T], output: &mut [Complex<T>]) -> Res<()> {
+        let mut scratch = self.make_scratch_vec();
+        self.process_with_scratch(input, output, &mut scratch)
+    }
+
+    fn process_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) -> Res<()> {
+        if input.len() != self.len() {
+            return Err(FftError::InputBuffer(self.len(), input.len()));
+        }
+        let bins = self.len() / 2 + 1;
+        if output.len() != bins {
+            return Err(FftError::OutputBuffer(bins, output.len()));
+        }
+        if scratch.len() < self.get_scratch_len() {
+            return Err(FftError::ScratchBuffer(
+                self.get_scratch_len(),
+                scratch.len(),
+            ));
+        }
+        for (val, w) in input.iter_mut().zip(self.window.iter()) {
+            *val = *val * *w;
+        }
+        self.fft.process_with_scratch(input, output, scratch)
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.fft.get_scratch_len()
+    }
+
+    fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    fn make_input_vec(&self) -> Vec<T> {
+        vec![T::zero(); self.len()]
+    }
+
+    fn make_output_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.len() / 2 + 1]
+    }
+
+    fn make_scratch_vec(&self) -> Vec<Complex<T>> {
+        vec![Complex::zero(); self.get_scratch_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FftError, RealFftPlanner, RealToComplex, Window, WindowedRealToComplex};
+
+    // Compare with reference values for periodic windows of length 5
+    #[test]
+    fn windows() {
+        let cases: [(Window, [f64; 5]); 5] = [
+            (
+                Window::Hann,
+                [
+                    0.0,
+                    0.345491502813,
+                    0.904508497187,
+                    0.904508497187,
+                    0.345491502813,
+                ],
+            ),
+            (
+                Window::Hamming,
+                [
+                    0.08,
+                    0.397852182588,
+                    0.912147817412,
+                    0.912147817412,
+                    0.397852182588,
+                ],
+            ),
+            (
+                Window::Blackman,
+                [
+                    0.0,
+                    0.200770143262,
+                    0.849229856738,
+                    0.849229856738,
+                    0.200770143262,
+                ],
+            ),
+            (
+                Window::Kaiser(8.0),
+                [
+                    0.002338831,
+                    0.226776841,
+                    0.859802082,
+                    0.859802082,
+                    0.226776841,
+                ],
+            ),
+            (
+                Window::FlatTop,
+                [
+                    -0.000421051,
+                    -0.067714252,
+                    0.606872153,
+                    0.606872153,
+                    -0.067714252,
+                ],
+            ),
+        ];
+        for (window, expected) in cases.iter() {
+            let values = window.periodic::<f64>(5);
+            for (e, v) in expected.iter().zip(values.iter()) {
+                assert!(
+                    (e - v).abs() < 1.0e-6,
+                    "{:?}: expected {}, got {}",
+                    window,
+                    e,
+                    v
+                );
+            }
+        }
+        let symmetric = Window::Hann.symmetric::<f64>(5);
+        for (e, v) in [0.0, 0.5, 1.0, 0.5, 0.0].iter().zip(symmetric.iter()) {
+            assert!((e - v).abs() < 1.0e-12);
+        }
+    }
+
+    // A sinusoid at the center of a bin has the same calibrated amplitude for all windows
+    #[test]
+    fn windowed_amplitude() {
+        let mut planner = RealFftPlanner::<f64>::new();
+        let len = 64;
+        for window in [
+            Window::Rectangular,
+            Window::Hann,
+            Window::Blackman,
+            Window::FlatTop,
+        ]
+        .iter()
+        {
+            let fft = WindowedRealToComplex::new(window.periodic(len), &mut planner);
+            let mut input = (0..len)
+                .map(|n| 3.0 * (2.0 * std::f64::consts::PI * 8.0 * n as f64 / len as f64).cos())
+                .collect::<Vec<f64>>();
+            let mut output = fft.make_output_vec();
+            fft.process(&mut input, &mut output).unwrap();
+            let amplitude = 2.0 * output[8].norm() / (len as f64 * fft.coherent_gain());
+            assert!(
+                (amplitude - 3.0).abs() < 1.0e-9,
+                "{:?}: amplitude {}",
+                window,
+                amplitude
+            );
+        }
+        let hann = WindowedRealToComplex::new(Window::Hann.periodic::<f64>(len), &mut planner);
+        assert!((hann.coherent_gain() - 0.5).abs() < 1.0e-12);
+        assert!((hann.enbw() - 1.5).abs() < 1.0e-12);
+
+        // The input is not windowed if the output has the wrong length
+        let mut input = vec![1.0; len];
+        let mut output = vec![Default::default(); len / 2];
+        let res = hann.process(&mut input, &mut output);
+        assert!(matches!(res, Err(FftError::OutputBuffer(33, 32))));
+        assert!(input.iter().all(|val| *val == 1.0));
+    }
+}
@@ -0,0 +1,289 @@
+use crate::rader::is_prime;
+use crate::{default_algorithm, SPLIT_RADICES};
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::iter;
+
+// The first line of the text format. The number is increased if the format changes.
+const HEADER: &str = "realfft-wisdom 1";
+
+/// The algorithm used by a real-to-complex or complex-to-real FFT of a given length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// For even lengths. The data is packed into a complex FFT of half the length.
+    HalfLength,
+    /// For any length. The data is transformed by a complex FFT of the full length.
+    FullLength,
+    /// For odd lengths divisible by the radix, which must be 3, 5, 7, 11 or 13.
+    /// The data is split into `radix` interleaved subsequences that are transformed pairwise by complex FFTs.
+    Split(usize),
+    /// For odd prime lengths. The data is reordered by the powers of a primitive root of the length,
+    /// which turns the FFT into a cyclic convolution that is calculated using real FFTs of the length minus one,
+    /// or of a zero-padded length when the length minus one has prime factors larger than 13.
+    Rader,
+}
+
+impl Algorithm {
+    /// Check if the algorithm can be used for an FFT of the given length.
+    pub fn supports(&self, len: usize) -> bool {
+        match self {
+            Algorithm::HalfLength => len % 2 == 0,
+            Algorithm::FullLength => len > 0,
+            Algorithm::Split(radix) => {
+                len % 2 == 1 && SPLIT_RADICES.contains(radix) && len % radix == 0
+            }
+            Algorithm::Rader => len % 2 == 1 && is_prime(len),
+        }
+    }
+
+    // Get the algorithms that can be used for the length.
+    pub(crate) fn candidates(len: usize) -> Vec<Algorithm> {
+        [Algorithm::HalfLength, Algorithm::FullLength]
+            .iter()
+            .cloned()
+            .chain(SPLIT_RADICES.iter().map(|radix| Algorithm::Split(*radix)))
+            .chain(iter::once(Algorithm::Rader))
+            .filter(|algorithm| algorithm.supports(len))
+            .collect()
+    }
+}
+
+/// Error returned when importing wisdom.
+pub enum WisdomError {
+    /// The text does not start with the header of a supported version of the format.
+    Header,
+    /// A line could not be parsed. The value is the line number, starting from 1.
+    Syntax(usize),
+    /// A line gives an algorithm that can't be used for the length. The value is the line number, starting from 1.
+    Unsupported(usize),
+}
+
+impl WisdomError {
+    fn fmt_internal(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let desc = match self {
+            Self::Header => format!("Wisdom must start with the line \"{}\"", HEADER),
+            Self::Syntax(line) => format!("Could not parse line {} of wisdom", line),
+            Self::Unsupported(line) => {
+                format!(
+                    "Unsupported algorithm for the length on line {} of wisdom",
+                    line
+                )
+            }
+        };
+        write!(f, "{}", desc)
+    }
+}
+
+impl fmt::Debug for WisdomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_internal(f)
+    }
+}
+
+impl fmt::Display for WisdomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_internal(f)
+    }
+}
+
+impl error::Error for WisdomError {}
+
+// An algorithm chosen for an FFT, and whether its inner complex FFTs are built by the scalar planner of RustFFT
+// instead of the default planner, which uses SIMD instructions when they are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Choice {
+    pub(crate) algorithm: Algorithm,
+    pub(crate) scalar: bool,
+}
+
+impl Choice {
+    // The choice that is used for a length when nothing has been measured or imported.
+    pub(crate) fn estimate(len: usize) -> Self {
+        Choice {
+            algorithm: default_algorithm(len),
+            scalar: false,
+        }
+    }
+}
+
+// The measured or imported choices for the forward and inverse FFTs of each length.
+// Choices that are estimated from the length alone are not stored.
+pub(crate) struct Wisdom {
+    pub(crate) forward: HashMap<usize, Choice>,
+    pub(crate) inverse: HashMap<usize, Choice>,
+}
+
+impl Wisdom {
+    pub(crate) fn new() -> Self {
+        Wisdom {
+            forward: HashMap::new(),
+            inverse: HashMap::new(),
+        }
+    }
+
+    // Write the wisdom in the text format, with one line per FFT sorted by direction and length, like:
+    //   forward 16 half
+    //   forward 6075 split 3
+    //   inverse 17 full scalar
+    pub(crate) fn export(&self) -> String {
+        let mut text = format!("{}\n", HEADER);
+        for (direction, algorithms) in
+            [("forward", &self.forward), ("inverse", &self.inverse)].iter()
+        {
+            let mut lengths: Vec<&usize> = algorithms.keys().collect();
+            lengths.sort_unstable();
+            for len in lengths {
+                let choice = algorithms[len];
+                let algorithm = match choice.algorithm {
+                    Algorithm::HalfLength => "half".to_string(),
+                    Algorithm::FullLength => "full".to_string(),
+                    Algorithm::Split(radix) => format!("split {}", radix),
+                    Algorithm::Rader => "rader".to_string(),
+                };
+                let planner = if choice.scalar { " scalar" } else { "" };
+                text.push_str(&format!("{} {} {}{}\n", direction, len, algorithm, planner));
+            }
+        }
+        text
+    }
+
+    // Parse wisdom from the text format. Nothing is returned unless all lines are valid.
+    pub(crate) fn parse(text: &str) -> Result<Wisdom, WisdomError> {
+        let mut lines = text.lines();
+        if lines.next().map(|line| line.trim()) != Some(HEADER) {
+            return Err(WisdomError::Header);
+        }
+        let mut wisdom = Wisdom::new();
+        for (idx, line) in lines.enumerate() {
+            let line_nbr = idx + 2;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            let len = match words.get(1).map(|word| word.parse::<usize>()) {
+                Some(Ok(len)) => len,
+                _ => return Err(WisdomError::Syntax(line_nbr)),
+            };
+            let scalar = words.last() == Some(&"scalar");
+            let end = if scalar { words.len() - 1 } else { words.len() };
+            let algorithm = match &words[2..end] {
+                ["half"] => Algorithm::HalfLength,
+                ["full"] => Algorithm::FullLength,
+                ["rader"] => Algorithm::Rader,
+                ["split", radix] => match radix.parse::<usize>() {
+                    Ok(radix) => Algorithm::Split(radix),
+                    Err(_) => return Err(WisdomError::Syntax(line_nbr)),
+                },
+                _ => return Err(WisdomError::Syntax(line_nbr)),
+            };
+            if !algorithm.supports(len) {
+                return Err(WisdomError::Unsupported(line_nbr));
+            }
+            let choice = Choice { algorithm, scalar };
+            match words[0] {
+                "forward" => wisdom.forward.insert(len, choice),
+                "inverse" => wisdom.inverse.insert(len, choice),
+                _ => return Err(WisdomError::Syntax(line_nbr)),
+            };
+        }
+        Ok(wisdom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RealFftPlanner, RealToReal, WisdomError};
+    use rand::Rng;
+
+    // Export the chosen algorithms, and import them in another planner
+    #[test]
+    fn export_import() {
+        // Algorithms estimated from the length are not exported
+        let mut planner = RealFftPlanner::<f64>::new();
+        planner.plan_fft_forward(6075);
+        planner.plan_fft_forward(16);
+        planner.plan_fft_inverse(1001);
+        assert_eq!(planner.export_wisdom(), "realfft-wisdom 1\n");
+
+        // Imported algorithms are exported again unchanged
+        let wisdom =
+            "realfft-wisdom 1\nforward 16 half\nforward 6075 split 3 scalar\ninverse 1001 full\n";
+        let mut imported = RealFftPlanner::<f64>::new();
+        imported.import_wisdom(wisdom).unwrap();
+        imported.plan_fft_forward(16);
+        imported.plan_fft_inverse(45);
+        assert_eq!(imported.export_wisdom(), wisdom);
+
+        // Force splitting and Rader's algorithm for short lengths, and full-length FFTs of even lengths,
+        // and compare with the default algorithms
+        let mut other = RealFftPlanner::<f64>::new();
+        other
+            .import_wisdom(
+                "realfft-wisdom 1\n\nforward 3 split 3\nforward 15 split 5 scalar\nforward 16 full\n\
+                forward 31 rader\nforward 77 split 11\ninverse 16 full scalar\ninverse 31 rader\n\
+                inverse 45 split 3\ninverse 91 split 13\ninverse 119 split 7\n",
+            )
+            .unwrap();
+        let mut rng = rand::thread_rng();
+        for &len in [3, 15, 16, 31, 77].iter() {
+            let fft = other.plan_fft_forward(len);
+            let reference = planner.plan_fft_forward(len);
+            let input: Vec<f64> = (0..len).map(|_| rng.gen::<f64>()).collect();
+            let mut output = fft.make_output_vec();
+            let mut expected = fft.make_output_vec();
+            fft.process(&mut input.clone(), &mut output).unwrap();
+            reference
+                .process(&mut input.clone(), &mut expected)
+                .unwrap();
+            for (val, exp) in output.iter().zip(expected.iter()) {
+                assert!((val - exp).norm() < 1.0e-9);
+            }
+        }
+        for &len in [16, 31, 45, 91, 119].iter() {
+            let ifft = other.plan_fft_inverse(len);
+            let reference = planner.plan_fft_inverse(len);
+            let mut input = ifft.make_input_vec();
+            for val in input.iter_mut().skip(1) {
+                val.re = rng.gen::<f64>();
+                val.im = rng.gen::<f64>();
+            }
+            if len % 2 == 0 {
+                input[len / 2].im = 0.0;
+            }
+            let mut output = ifft.make_output_vec();
+            let mut expected = ifft.make_output_vec();
+            ifft.process(&mut input.clone(), &mut output).unwrap();
+            reference.process(&mut input, &mut expected).unwrap();
+            for (val, exp) in output.iter().zip(expected.iter()) {
+                assert!((val - exp).abs() < 1.0e-9);
+            }
+        }
+        assert!(other
+            .export_wisdom()
+            .contains("forward 15 split 5 scalar\n"));
+
+        // Invalid wisdom is rejected as a whole
+        let res = other.import_wisdom("forward 16 half\n");
+        assert!(matches!(res, Err(WisdomError::Header)));
+        let res = other.import_wisdom("realfft-wisdom 1\nforward 17 full\nforward 16 quarter\n");
+        assert!(matches!(res, Err(WisdomError::Syntax(3))));
+        let res = other.import_wisdom("realfft-wisdom 1\ninverse 21 split 5\n");
+        assert!(matches!(res, Err(WisdomError::Unsupported(2))));
+        let res = other.import_wisdom("realfft-wisdom 1\nforward 33 rader\n");
+        assert!(matches!(res, Err(WisdomError::Unsupported(2))));
+        assert!(!other.export_wisdom().contains("forward 17"));
+
+        // Cached transforms that wrap a forward FFT with another algorithm are planned again
+        let mut cached = RealFftPlanner::<f64>::new();
+        let dht = cached.plan_dht(16);
+        cached
+            .import_wisdom("realfft-wisdom 1\nforward 16 full\n")
+            .unwrap();
+        let replanned = cached.plan_dht(16);
+        assert_ne!(
+            &*dht as *const dyn RealToReal<f64> as *const u8,
+            &*replanned as *const dyn RealToReal<f64> as *const u8
+        );
+    }
+}